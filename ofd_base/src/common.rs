@@ -414,7 +414,7 @@ pub struct CtColorSpace {
     pub palette: Palette,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Palette {
     /// this is a table
     /// 0 -> color_value1
@@ -495,6 +495,7 @@ pub struct CtFont {
 mod tests {
     use super::*;
 
+    #[allow(dead_code)]
     #[derive(Debug, Deserialize, PartialEq)]
     struct TestStruct {
         #[serde(flatten)]
@@ -5,13 +5,13 @@ use crate::base::{StBox, StId, StLoc, StRefId};
 
 use super::page::VtGraphicUnit;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AnnotationsXmlFile {
     #[serde(rename = "Page")]
     pub page: Option<Vec<Page>>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Page {
     #[serde(rename = "@PageID")]
     pub page_id: StRefId,
@@ -20,7 +20,7 @@ pub struct Page {
     pub file_loc: StLoc,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AnnotationXmlFile {
     #[serde(rename = "Annot")]
     pub annot: Vec<Annot>,
@@ -33,7 +33,7 @@ fn zero_date() -> NaiveDate {
     NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Annot {
     #[serde(rename = "@ID")]
     pub id: StId,
@@ -80,7 +80,7 @@ pub struct Annot {
     pub appearance: Appearance,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Appearance {
     #[serde(rename = "@Boundary")]
     pub boundary: StBox,
@@ -88,13 +88,13 @@ pub struct Appearance {
     pub objects: Option<Vec<VtGraphicUnit>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Parameters {
     #[serde(rename = "Parameter")]
     pub parameters: Vec<Parameter>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Parameter {
     #[serde(rename = "@Name")]
     pub name: String,
@@ -3,13 +3,13 @@ use serde::{Deserialize, Serialize};
 
 use crate::base::StLoc;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AttachmentsXmlFile {
     #[serde(rename = "Attachment")]
     pub attachments: Option<Vec<Attachment>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Attachment {
     #[serde(rename = "@ID")]
     pub id: String,
@@ -2,12 +2,12 @@ use serde::{Deserialize, Serialize};
 
 use crate::base::StLoc;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CustomTagsXmlFile {
     #[serde(rename = "CustomTag")]
     pub custom_tags: Option<Vec<CustomTag>>,
 }
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CustomTag {
     #[serde(rename = "@TypeID")]
     pub type_id: String,
@@ -1,31 +1,53 @@
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use xdom::RawElement;
 
 use crate::base::{StLoc, StRefId};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ExtensionXmlFile {
+    #[serde(rename = "Extension")]
     pub extensions: Option<Vec<Extension>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Extension {
+    #[serde(rename = "@AppName")]
     pub app_name: String,
+
+    #[serde(rename = "@Company")]
     pub company: Option<String>,
+
+    #[serde(rename = "@AppVersion")]
     pub app_version: Option<String>,
+
+    #[serde(rename = "@Date")]
     pub date: Option<NaiveDateTime>,
+
+    /// the object this extension applies to
+    #[serde(rename = "@RefId")]
     pub ref_id: StRefId,
+
+    #[serde(rename = "$value")]
     pub props: Vec<Prop>,
 }
-#[derive(Debug, Serialize, Deserialize)]
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum Prop {
     Property {
+        #[serde(rename = "@Name")]
         name: String,
+
+        #[serde(rename = "@Type")]
         r#type: Option<String>,
+
+        #[serde(rename = "$text")]
         value: String,
     },
-    Data(
-        // TODO: ANY_TYPE
-    ),
+
+    /// `xs:anyType`, the whole `Data` element is kept
+    Data(RawElement),
+
+    /// location of an external file holding the data
     ExtendData(StLoc),
 }
@@ -8,7 +8,7 @@ use crate::{
 
 use super::page::VtGraphicUnit;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ResourceXmlFile {
     #[serde(rename = "@BaseLoc")]
     pub base_loc: StLoc,
@@ -17,7 +17,7 @@ pub struct ResourceXmlFile {
     pub resources: Option<Vec<Resource>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum Resource {
     ColorSpaces(ColorSpaces),
     DrawParams(DrawParams),
@@ -25,13 +25,13 @@ pub enum Resource {
     MultiMedias(MultiMedias),
     CompositeGraphicUnits(CompositeGraphicUnits),
 }
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ColorSpaces {
     #[serde(rename = "ColorSpace")]
     pub color_spaces: Vec<ColorSpace>,
 }
 
-#[derive(Debug, Serialize, Deserialize, EnumString)]
+#[derive(Debug, Serialize, Deserialize, EnumString, Clone)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum Type {
     RGB,
@@ -47,7 +47,7 @@ impl Type {
         }
     }
 }
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ColorSpace {
     #[serde(rename = "@ID")]
     pub id: StId,
@@ -74,7 +74,7 @@ pub static SRGB: ColorSpace = ColorSpace {
     palette: None,
 };
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DrawParams {
     #[serde(rename = "DrawParam")]
     pub draw_params: Vec<DrawParam>,
@@ -118,7 +118,7 @@ pub struct DrawParam {
     pub stroke_color: Option<CtColor>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Fonts {
     #[serde(rename = "Font")]
     pub fonts: Vec<Font>,
@@ -158,13 +158,13 @@ pub struct Font {
     pub font_file: Option<StLoc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MultiMedias {
     #[serde(rename = "MultiMedia")]
     pub multi_medias: Vec<MultiMedia>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MultiMedia {
     #[serde(rename = "@ID")]
     pub id: StId,
@@ -180,20 +180,20 @@ pub struct MultiMedia {
 }
 
 // #[strum]
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, EnumString)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Hash, EnumString, Clone)]
 pub enum MultiMediaType {
     Video,
     Audio,
     Image,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CompositeGraphicUnits {
     #[serde(rename = "CompositeGraphicUnit")]
     pub composite_graphic_units: Vec<CompositeGraphicUnit>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CompositeGraphicUnit {
     #[serde(rename = "@ID")]
     pub id: StId,
@@ -202,7 +202,7 @@ pub struct CompositeGraphicUnit {
     pub base: CtVectorG,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CtVectorG {
     #[serde(rename = "@Width")]
     pub width: f32,
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::base::StLoc;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct VersionXmlFile {
     #[serde(rename = "@ID")]
     pub id: String,
@@ -24,16 +24,16 @@ pub struct VersionXmlFile {
     pub doc_root: StLoc,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FileList {
     #[serde(rename = "File")]
     pub files: Vec<File>,
 }
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct File {
     #[serde(rename = "@ID")]
     pub id: String,
-    #[serde(rename = "$value")]
+    #[serde(rename = "$text")]
     pub path: StLoc,
 }
 #[cfg(test)]
@@ -52,6 +52,19 @@ enum Commands {
         #[arg(short, long, default_value_t = false)]
         template: bool,
     },
+    /// list or extract attachments of a document
+    Attachments {
+        /// file path
+        ofd_file: PathBuf,
+
+        /// doc index
+        #[arg(default_value_t = 0)]
+        doc_index: usize,
+
+        /// extract attachments into this dir
+        #[arg(short, long)]
+        out_path: Option<PathBuf>,
+    },
 }
 
 fn init_logger() {
@@ -112,6 +125,18 @@ fn main() -> Result<()> {
                 ofd_utils::render_ofd(&ofd_file, &out_path, &path_template)?;
             }
         }
+        Commands::Attachments {
+            ofd_file,
+            doc_index,
+            out_path,
+        } => {
+            let attachments = ofd_utils::get_attachments(&ofd_file, doc_index)?;
+            println!("This document has {} attachment(s).", attachments.len());
+            print_stdout(attachments.with_title())?;
+            if let Some(out_path) = out_path {
+                ofd_utils::extract_attachments(&ofd_file, doc_index, &out_path)?;
+            }
+        }
     }
 
     Ok(())
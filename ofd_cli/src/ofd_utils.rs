@@ -1,12 +1,11 @@
 use cli_table::Table;
-use eyre::{OptionExt, Result};
+use eyre::{eyre, OptionExt, Result};
 use interpolator::{format, Formattable};
-use ofd_base::file::document::DocumentXmlFile;
 use ofd_conv::img::render;
-use ofd_rw::{self, Ofd};
+use ofd_rw::{self, Ofd, SharedOfd, Storage};
 use std::collections::HashMap;
-use std::io::{Read, Seek};
 use std::path::Path;
+use std::thread;
 use std::time::Instant;
 use std::{
     fs::{create_dir_all, File},
@@ -58,10 +57,10 @@ pub fn get_info(path: &PathBuf) -> Result<OfdInfo> {
         .map(|(idx, ele)| {
             let doc_id = ele.doc_info.doc_id.to_owned();
 
-            let xml: DocumentXmlFile = container.document_by_index(idx)?.content;
+            let xml = container.document_by_index(idx)?.content;
             let page_count = xml.pages.page.len();
 
-            let template_count = match xml.common_data.template_page {
+            let template_count = match &xml.common_data.template_page {
                 Some(v) => v.len(),
                 None => 0,
             };
@@ -80,7 +79,59 @@ pub fn get_info(path: &PathBuf) -> Result<OfdInfo> {
     })
 }
 
-fn get_doc_count<I: Read + Seek>(container: &Ofd<I>) -> Result<usize> {
+fn fmt_option(v: &Option<String>) -> String {
+    v.clone().unwrap_or_default()
+}
+
+#[derive(Debug, Table)]
+pub struct AttachmentInfo {
+    #[table(title = "id")]
+    pub id: String,
+
+    #[table(title = "name")]
+    pub name: String,
+
+    #[table(title = "format", display_fn = "fmt_option")]
+    pub format: Option<String>,
+
+    /// path in package
+    #[table(title = "path")]
+    pub path: String,
+}
+
+pub fn get_attachments(path: &PathBuf, doc_index: usize) -> Result<Vec<AttachmentInfo>> {
+    let container = ofd_rw::from_path(path)?;
+    let attachments = container
+        .attachments_for_doc(doc_index)?
+        .into_iter()
+        .map(|a| AttachmentInfo {
+            path: a.path().to_string(),
+            id: a.content.id.clone(),
+            name: a.content.name.clone(),
+            format: a.content.format.clone(),
+        })
+        .collect();
+    Ok(attachments)
+}
+
+/// write each attachment into `out_dir`, named after its file in package
+pub fn extract_attachments(path: &PathBuf, doc_index: usize, out_dir: &Path) -> Result<()> {
+    let container = ofd_rw::from_path(path)?;
+    create_dir_all(out_dir)?;
+    for attachment in container.attachments_for_doc(doc_index)? {
+        let file_name = attachment
+            .path()
+            .file_name()
+            .ok_or_eyre("attachment without file name")?;
+        let out = out_dir.join(file_name);
+        info!("extracting {} to {}", attachment.path(), out.display());
+        let mut file = File::create(out)?;
+        container.copy_item_to(attachment.path().as_str(), &mut file)?;
+    }
+    Ok(())
+}
+
+fn get_doc_count<I: Storage>(container: &Ofd<I>) -> Result<usize> {
     let item = container.entry()?;
 
     let xml = item.content;
@@ -88,8 +139,8 @@ fn get_doc_count<I: Read + Seek>(container: &Ofd<I>) -> Result<usize> {
     Ok(doc_count)
 }
 
-fn get_page_count<I: Read + Seek>(container: &Ofd<I>, doc_index: usize) -> Result<usize> {
-    let xml: DocumentXmlFile = container.document_by_index(doc_index)?.content;
+fn get_page_count<I: Storage>(container: &Ofd<I>, doc_index: usize) -> Result<usize> {
+    let xml = container.document_by_index(doc_index)?.content;
     let page_count = xml.pages.page.len();
     Ok(page_count)
 }
@@ -154,13 +205,15 @@ pub fn render_page(
 // fn create_dir()
 
 // render all pages for doc_index
+// pages are spread over worker threads, each worker has its own render
 pub(crate) fn render_doc(
     ofd_path: &PathBuf,
     out_dir_path: &Path,
     doc_index: usize,
     path_template: &str,
 ) -> Result<()> {
-    let res = ofd_rw::from_path(ofd_path)?;
+    let shared = SharedOfd::from_path(ofd_path)?;
+    let res = shared.ofd();
 
     let doc_count = get_doc_count(&res)?;
 
@@ -171,26 +224,43 @@ pub(crate) fn render_doc(
     );
     let page_count = get_page_count(&res, doc_index)?;
 
-    let mut render = render::Render::new(res, "楷体")?;
-    for pid in 0..page_count {
-        info!("rendering doc {} page {}", doc_index, pid);
-        let mut i = render.render_page(doc_index, pid)?;
-        let img = i.image_snapshot();
-        let data = img
-            .encode(None, ofd_conv::img::EncodedImageFormat::PNG, 100)
-            .ok_or_eyre("can not encode image to png!")?;
-
-        write_image(
-            &data,
-            path_template,
-            ofd_path,
-            out_dir_path,
-            doc_index,
-            pid,
-            "png",
-        )?;
-    }
-    Ok(())
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(page_count.max(1));
+    thread::scope(|s| {
+        let handles = (0..workers)
+            .map(|worker| {
+                let shared = shared.clone();
+                s.spawn(move || -> Result<()> {
+                    let mut render = render::Render::new(shared.ofd(), "楷体")?;
+                    for pid in (worker..page_count).step_by(workers) {
+                        info!("rendering doc {} page {}", doc_index, pid);
+                        let mut i = render.render_page(doc_index, pid)?;
+                        let img = i.image_snapshot();
+                        let data = img
+                            .encode(None, ofd_conv::img::EncodedImageFormat::PNG, 100)
+                            .ok_or_eyre("can not encode image to png!")?;
+
+                        write_image(
+                            &data,
+                            path_template,
+                            ofd_path,
+                            out_dir_path,
+                            doc_index,
+                            pid,
+                            "png",
+                        )?;
+                    }
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|h| h.join().map_err(|_| eyre!("render worker panicked"))?)
+            .collect::<Result<()>>()
+    })
 }
 
 pub(crate) fn render_ofd(p0: &PathBuf, p1: &Path, path_template: &str) -> Result<()> {
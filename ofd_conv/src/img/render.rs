@@ -4,7 +4,7 @@ mod font;
 mod path;
 mod text;
 
-use std::io::{Cursor, Read, Seek};
+use std::io::Cursor;
 use std::iter::Enumerate;
 use std::slice::Iter;
 use std::str::FromStr;
@@ -39,7 +39,7 @@ use ofd_base::file::res::SRGB;
 use ofd_base::StArray;
 use ofd_base::StBox;
 use ofd_base::StRefId;
-use ofd_rw::{from_bytes, Ofd, Resources};
+use ofd_rw::{from_bytes, Ofd, Resources, Storage};
 use ofd_sign::decode_sign;
 
 struct RenderCtx<'a, I> {
@@ -57,7 +57,7 @@ pub struct Render<I> {
     font_mgr: AggFontMgr<I>,
 }
 
-impl<I: Read + Seek> Render<I> {
+impl<I: Storage> Render<I> {
     pub fn new(ofd: Ofd<I>, fallback_font: impl AsRef<str>) -> Result<Self> {
         // let o = ofd.0.clone()
         let font_mgr = AggFontMgr::builder(ofd.clone(), fallback_font).build()?;
@@ -88,7 +88,7 @@ impl<I: Read + Seek> Render<I> {
         let templates = self.ofd.templates_for_page(doc_index, page_index)?;
         let template_pages = &templates
             .iter()
-            .map(|i| &i.content)
+            .map(|i| &*i.content)
             .collect::<Vec<&PageXmlFile>>();
         let pa = decide_size(page_xml, template_pages, doc_xml);
         let size = (
@@ -143,7 +143,7 @@ impl<I: Read + Seek> Render<I> {
                     }
 
                     // ctx.canvas.cl
-                    let path = sign_file.resolve(&sign_file.signed_value);
+                    let path = sign_file.resolve(&sign_file.signed_value)?;
 
                     let sign_bytes = self.ofd.bytes(path)?;
                     let s = decode_sign(&sign_bytes)?;
@@ -194,7 +194,7 @@ impl<I: Read + Seek> Render<I> {
         let templates = self.ofd.templates_for_page(doc_index, page_index)?;
         let template_pages = &templates
             .iter()
-            .map(|i| &i.content)
+            .map(|i| &*i.content)
             .collect::<Vec<&PageXmlFile>>();
         let pa = decide_size(page_xml, template_pages, doc_xml);
         let size = (
@@ -248,7 +248,7 @@ impl<I: Read + Seek> Render<I> {
         let templates = self.ofd.templates_for_page(doc_index, page_index)?;
         let template_pages = &templates
             .iter()
-            .map(|i| &i.content)
+            .map(|i| &*i.content)
             .collect::<Vec<&PageXmlFile>>();
         let pa = decide_size(page_xml, template_pages, doc_xml);
         let size = (
@@ -281,9 +281,25 @@ impl<I: Read + Seek> Render<I> {
         can.restore();
         Ok(sur)
     }
+
+    /// fill translucent rectangles over a rendered page, such as search hits.
+    /// `rects` are in millimetres of the page
+    pub fn draw_highlights(&self, surface: &mut Surface, rects: &[StBox], color: Color4f) {
+        let can = surface.canvas();
+        can.save();
+        let scale = calc_scale(self.dpi);
+        can.scale((scale, scale));
+        let mut paint = Paint::new(color, None);
+        paint.set_anti_alias(true);
+        paint.set_blend_mode(BlendMode::Multiply);
+        for rect in rects {
+            can.draw_rect(Rect::from_xywh(rect.x, rect.y, rect.w, rect.h), &paint);
+        }
+        can.restore();
+    }
 }
 
-fn draw_anno<I: Read + Seek>(ctx: &mut RenderCtx<I>, anno: &AnnotationXmlFile) -> Result<()> {
+fn draw_anno<I: Storage>(ctx: &mut RenderCtx<I>, anno: &AnnotationXmlFile) -> Result<()> {
     for annot in &anno.annot {
         if !annot.visible.unwrap_or(true) {
             continue;
@@ -555,7 +571,7 @@ fn decide_size(
 }
 
 /// draw a page
-fn draw_page<I: Read + Seek>(ctx: &mut RenderCtx<I>, tpl: &PageXmlFile) -> Result<()> {
+fn draw_page<I: Storage>(ctx: &mut RenderCtx<I>, tpl: &PageXmlFile) -> Result<()> {
     let init_sc = ctx.canvas.save_count();
     if let Some(content) = tpl.content.as_ref() {
         for layer in &content.layer {
@@ -586,7 +602,7 @@ fn get_draw_param_by_id(resources: &Resources, id: Option<StRefId>) -> Option<Dr
     }
 }
 
-fn draw_layer<I: Read + Seek>(ctx: &mut RenderCtx<I>, layer: &ofd_base::file::page::Layer) {
+fn draw_layer<I: Storage>(ctx: &mut RenderCtx<I>, layer: &ofd_base::file::page::Layer) {
     let resources = ctx.resources;
     if let Some(dp_id) = layer.draw_param {
         let dp = resources.get_draw_param_by_id(dp_id);
@@ -600,7 +616,7 @@ fn draw_layer<I: Read + Seek>(ctx: &mut RenderCtx<I>, layer: &ofd_base::file::pa
     }
 }
 
-fn draw_object<I: Read + Seek>(ctx: &mut RenderCtx<I>, objects: &Vec<VtGraphicUnit>) {
+fn draw_object<I: Storage>(ctx: &mut RenderCtx<I>, objects: &Vec<VtGraphicUnit>) {
     let canvas = ctx.canvas;
     let resources = ctx.resources;
 
@@ -642,10 +658,7 @@ fn draw_object<I: Read + Seek>(ctx: &mut RenderCtx<I>, objects: &Vec<VtGraphicUn
 
 // impl ToMatrix for
 
-fn draw_image_object<I: Read + Seek>(
-    ctx: &mut RenderCtx<I>,
-    image_object: &ImageObject,
-) -> Result<()> {
+fn draw_image_object<I: Storage>(ctx: &mut RenderCtx<I>, image_object: &ImageObject) -> Result<()> {
     if !image_object.visible.unwrap_or(true) {
         return Ok(());
     }
@@ -656,7 +669,7 @@ fn draw_image_object<I: Read + Seek>(
         match &image.format {
             Some(format) => match format.to_lowercase().as_str() {
                 "png" => {
-                    let p = ofd_item.resolve(&ofd_item.base_loc.join(&image.media_file));
+                    let p = ofd_item.resolve(&ofd_item.base_loc.join(&image.media_file))?;
                     // dbg!(&p);
                     let bytes = ctx._ofd.bytes(p)?;
                     let len = bytes.len();
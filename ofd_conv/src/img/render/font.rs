@@ -1,5 +1,4 @@
 use skia_safe::{FontMgr, FontStyle, Typeface};
-use std::io::{Read, Seek};
 use std::path::Path;
 use std::{
     collections::{HashMap, HashSet},
@@ -11,7 +10,7 @@ use walkdir::WalkDir;
 use crate::error::MyError;
 use eyre::{eyre, Result};
 use ofd_base::{StId, StRefId};
-use ofd_rw::Ofd;
+use ofd_rw::{Ofd, Storage};
 use tracing::{debug, warn};
 
 pub(super) struct LocalDirFontMgr {
@@ -134,7 +133,7 @@ struct EmbeddedFontMgr<I> {
     font_cache: HashMap<String, Typeface>,
 }
 
-impl<I: Read + Seek> EmbeddedFontMgr<I> {
+impl<I: Storage> EmbeddedFontMgr<I> {
     pub(crate) fn load_embed_font(
         &mut self,
         path: impl AsRef<str> + Into<String>,
@@ -202,7 +201,7 @@ impl<I> AggFontMgr<I> {
     }
 }
 
-impl<I: Read + Seek> AggFontMgr<I> {
+impl<I: Storage> AggFontMgr<I> {
     pub(crate) fn fallback_typeface(&self) -> Typeface {
         self.fallback.clone()
     }
@@ -225,7 +224,7 @@ impl<I: Read + Seek> AggFontMgr<I> {
     }
 }
 
-impl<I: Read + Seek> AggFontMgr<I> {
+impl<I: Storage> AggFontMgr<I> {
     pub(super) fn builder(
         ofd: Ofd<I>,
         fallback_font_name: impl AsRef<str>,
@@ -240,7 +239,7 @@ pub(super) struct AggFontMgrBuilder<I> {
     fallback_font_name: String,
 }
 
-impl<I: Read + Seek> AggFontMgrBuilder<I> {
+impl<I: Storage> AggFontMgrBuilder<I> {
     pub fn new(ofd: Ofd<I>, fallback_font_name: impl AsRef<str>) -> Self {
         Self {
             ofd,
@@ -2,17 +2,16 @@ use crate::img::render::{apply_boundary, apply_ctm, next_val, RenderCtx};
 use eyre::OptionExt;
 use ofd_base::file::page::TextObject;
 use ofd_base::StArray;
-use ofd_rw::Resources;
+use ofd_rw::{Resources, Storage};
 use skia_safe::{Color, Font, FontStyle, Paint, Point, TextBlob, TextBlobBuilder};
 use std::cmp::max;
 use std::collections::HashMap;
-use std::io::{Read, Seek};
 use std::ops::Index;
 use std::path::PathBuf;
 use std::str::FromStr;
 use tracing::{debug, warn};
 
-pub(super) fn draw_text_object<I: Read + Seek>(
+pub(super) fn draw_text_object<I: Storage>(
     ctx: &mut RenderCtx<I>,
     text_object: &TextObject,
 ) -> eyre::Result<()> {
@@ -89,7 +88,7 @@ pub(super) fn draw_text_object<I: Read + Seek>(
     Ok(())
 }
 
-fn get_font<I: Read + Seek>(
+fn get_font<I: Storage>(
     ctx: &mut RenderCtx<I>,
     text_object: &TextObject,
     resources: &Resources,
@@ -100,7 +99,7 @@ fn get_font<I: Read + Seek>(
     let typeface = if let Some((file, font)) = file_and_font {
         if let Some(font_file) = font.font_file.as_ref() {
             debug!("embedded font file: {}", font_file.display());
-            let p = file.resolve(&PathBuf::from(&file.content.base_loc).join(font_file));
+            let p = file.resolve(&PathBuf::from(&file.content.base_loc).join(font_file))?;
             debug!("embedded font file: {}", p);
             ctx.font_mgr.load_embed_font(p)?
         } else {
@@ -0,0 +1,146 @@
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+/// 64 MiB of xml
+pub(crate) const DEFAULT_CACHE_BUDGET: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+struct CacheKey {
+    path: String,
+    tid: TypeId,
+}
+
+struct Slot {
+    value: Rc<dyn Any>,
+    size: usize,
+    // last use, key of `ItemCache::recent`
+    tick: u64,
+}
+
+/// parsed items by path and type, the least recently used ones are
+/// evicted when the sizes add up over the budget
+///
+/// the size of an item is the size of the xml it is parsed from.
+pub(crate) struct ItemCache {
+    slots: HashMap<CacheKey, Slot>,
+    // keys by last use, the oldest first
+    recent: BTreeMap<u64, CacheKey>,
+    tick: u64,
+    used: usize,
+    budget: usize,
+}
+
+impl ItemCache {
+    pub(crate) fn new(budget: usize) -> Self {
+        Self {
+            slots: HashMap::new(),
+            recent: BTreeMap::new(),
+            tick: 0,
+            used: 0,
+            budget,
+        }
+    }
+
+    pub(crate) fn get<T: 'static>(&mut self, path: &str) -> Option<Rc<T>> {
+        let key = CacheKey {
+            path: path.into(),
+            tid: TypeId::of::<T>(),
+        };
+        let slot = self.slots.get_mut(&key)?;
+        self.tick += 1;
+        self.recent.remove(&slot.tick);
+        slot.tick = self.tick;
+        let value = slot.value.clone().downcast::<T>().ok();
+        self.recent.insert(self.tick, key);
+        value
+    }
+
+    /// items larger than the whole budget are not kept
+    pub(crate) fn insert<T: 'static>(&mut self, path: &str, value: Rc<T>, size: usize) {
+        let key = CacheKey {
+            path: path.into(),
+            tid: TypeId::of::<T>(),
+        };
+        self.remove(&key);
+        if size > self.budget {
+            return;
+        }
+        self.tick += 1;
+        self.used += size;
+        self.recent.insert(self.tick, key.clone());
+        let tick = self.tick;
+        self.slots.insert(key, Slot { value, size, tick });
+        self.evict();
+    }
+
+    pub(crate) fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict();
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.slots.clear();
+        self.recent.clear();
+        self.used = 0;
+    }
+
+    /// total size of the items kept
+    pub(crate) fn used(&self) -> usize {
+        self.used
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some(slot) = self.slots.remove(key) {
+            self.recent.remove(&slot.tick);
+            self.used -= slot.size;
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.used > self.budget {
+            let Some((_, key)) = self.recent.pop_first() else {
+                break;
+            };
+            if let Some(slot) = self.slots.remove(&key) {
+                self.used -= slot.size;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru() {
+        let mut cache = ItemCache::new(10);
+        cache.insert("a", Rc::new(1_u8), 4);
+        cache.insert("b", Rc::new(2_u8), 4);
+        // same path, another type
+        assert!(cache.get::<u16>("a").is_none());
+        // `a` is used more recently than `b`
+        assert_eq!(cache.get::<u8>("a").as_deref(), Some(&1));
+        cache.insert("c", Rc::new(3_u8), 4);
+        assert!(cache.get::<u8>("b").is_none());
+        assert_eq!(cache.get::<u8>("a").as_deref(), Some(&1));
+        assert_eq!(cache.get::<u8>("c").as_deref(), Some(&3));
+        assert_eq!(cache.used(), 8);
+
+        // replacing keeps the size right
+        cache.insert("c", Rc::new(4_u8), 2);
+        assert_eq!(cache.used(), 6);
+
+        cache.insert("big", Rc::new(5_u8), 11);
+        assert!(cache.get::<u8>("big").is_none());
+        assert_eq!(cache.used(), 6);
+
+        cache.set_budget(4);
+        assert!(cache.get::<u8>("a").is_none());
+        assert_eq!(cache.get::<u8>("c").as_deref(), Some(&4));
+        cache.clear();
+        assert_eq!(cache.used(), 0);
+        assert!(cache.get::<u8>("c").is_none());
+    }
+}
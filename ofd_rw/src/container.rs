@@ -1,8 +1,22 @@
+use crate::cache::{ItemCache, DEFAULT_CACHE_BUDGET};
+use crate::custom_tag::{read_tag_file, TagElement, TagFile};
 use crate::error::{Error, Result};
+use crate::id_index::{index_document, IdIndex, IdTarget, IndexLoader, ResourceKind};
+use crate::limits::Limits;
+use crate::outline::{self, BookmarkEntry, OutlineEntry};
+use crate::repair::{self, Recovery, RecoveryKind};
+use crate::storage::{DirStorage, Storage};
+use crate::writer::OfdWriter;
 use minidom::Element;
 use ofd_base::file::annotation::{AnnotationXmlFile, AnnotationsXmlFile};
-use ofd_base::file::res::{MultiMedia, MultiMediaType, Resource};
+use ofd_base::file::attachment::{Attachment, AttachmentsXmlFile};
+use ofd_base::file::custom_tag::CustomTagsXmlFile;
+use ofd_base::file::extension::{Extension, ExtensionXmlFile, Prop};
+use ofd_base::file::ofd::Version;
+use ofd_base::file::page::VtGraphicUnit;
+use ofd_base::file::res::{CompositeGraphicUnit, MultiMedia, MultiMediaType, Resource};
 use ofd_base::file::signature::{SignatureXmlFile, SignaturesXmlFile, StampAnnot};
+use ofd_base::file::version::VersionXmlFile;
 use ofd_base::{
     file::{
         document::DocumentXmlFile,
@@ -10,32 +24,41 @@ use ofd_base::{
         page::PageXmlFile,
         res::{ColorSpace, DrawParam, Font, ResourceXmlFile},
     },
-    StRefId,
+    StLoc, StRefId,
 };
-use relative_path::{PathExt, RelativePathBuf};
+use relative_path::{Component, RelativePath, RelativePathBuf};
 use serde::de::DeserializeOwned;
-use std::any::{Any, TypeId};
 use std::cell::RefCell;
-use std::collections::HashMap;
-use std::io::{BufRead, Cursor, Read, Seek};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufRead, Cursor, Read, Seek, Write};
 use std::ops::Deref;
 use std::path::Path;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::{fs::File, io::BufReader, path::PathBuf};
 use tracing::debug;
-use zip::{read::ZipFile, ZipArchive};
-
-#[derive(Eq, Hash, PartialEq)]
-struct CacheKey {
-    path: String,
-    tid: TypeId,
-}
+use zip::ZipArchive;
 
 // pub type  Ofd =  Rc<RefCell<RawOfd>>;
 
-struct RawOfd<R> {
-    zip_archive: ZipArchive<R>,
-    cache: HashMap<CacheKey, Box<dyn Any>>,
+pub(crate) struct RawOfd<S> {
+    storage: S,
+    cache: ItemCache,
+    // `DocRoot` of the selected versions, by doc index
+    version_roots: HashMap<usize, RelativePathBuf>,
+    // files of the selected versions by the items they stand for, by doc index
+    version_files: HashMap<usize, HashMap<RelativePathBuf, RelativePathBuf>>,
+    lenient: bool,
+    recoveries: Vec<Recovery>,
+    // repaired xml items, by item name
+    repaired: BTreeMap<String, Vec<u8>>,
+    // `Document.xml` found by scanning in lenient mode, by doc index
+    located_roots: BTreeMap<usize, RelativePathBuf>,
+    limits: Limits,
+    // bytes read from all the items, for `Limits::max_total_size`,
+    // shared by the views of a `SharedOfd`
+    bytes_read: Arc<AtomicU64>,
 }
 
 // #[derive(Clone)]
@@ -47,8 +70,8 @@ impl<I> Clone for Ofd<I> {
     }
 }
 
-impl<R: Read + Seek> Ofd<R> {
-    fn from_raw(raw_ofd: RawOfd<R>) -> Self {
+impl<S: Storage> Ofd<S> {
+    pub(crate) fn from_raw(raw_ofd: RawOfd<S>) -> Self {
         Self(Rc::new(RefCell::new(raw_ofd)))
     }
 
@@ -60,6 +83,31 @@ impl<R: Read + Seek> Ofd<R> {
         self.0.borrow_mut().document_by_index(doc_index)
     }
 
+    /// versions of a document, ordered by `Index`
+    pub fn versions_for_doc(&self, doc_index: usize) -> Result<DocVersions> {
+        self.0.borrow_mut().versions_for_doc(doc_index)
+    }
+
+    /// the version marked as `Current`
+    pub fn current_version(
+        &self,
+        doc_index: usize,
+    ) -> Result<Option<(Version, OfdItem<VersionXmlFile>)>> {
+        let versions = self.versions_for_doc(doc_index)?;
+        Ok(versions
+            .into_iter()
+            .find(|(v, _)| v.current.unwrap_or(false)))
+    }
+
+    /// read a document as of a version, `None` goes back to `DocRoot` of `OFD.xml`
+    ///
+    /// the files listed by the version are read in place of the items at the same place
+    /// under the folder of the document.
+    /// the selection is shared by all the clones of this [Ofd].
+    pub fn select_version(&self, doc_index: usize, version_id: Option<&str>) -> Result<()> {
+        self.0.borrow_mut().select_version(doc_index, version_id)
+    }
+
     pub fn page_by_index(
         &self,
         doc_index: usize,
@@ -95,17 +143,97 @@ impl<R: Read + Seek> Ofd<R> {
     }
 
     pub fn item_names(&self) -> Vec<String> {
-        self.0
-            .borrow_mut()
-            .item_names()
-            .map(str::to_owned)
-            .collect::<Vec<_>>()
+        self.0.borrow().item_names()
+    }
+
+    /// does the package contain an item
+    pub fn contains(&self, path: impl AsRef<str>) -> bool {
+        self.0.borrow().storage.contains(path.as_ref())
     }
 
     pub fn bytes(&self, path: impl AsRef<str> + Into<String>) -> Result<Vec<u8>> {
         self.0.borrow_mut().bytes(path)
     }
 
+    /// stream an item into `writer`, returns the number of bytes copied
+    pub fn copy_item_to<W: Write>(
+        &self,
+        path: impl AsRef<str> + Into<String>,
+        writer: &mut W,
+    ) -> Result<u64> {
+        self.0.borrow_mut().copy_item_to(path, writer)
+    }
+
+    pub fn attachments_for_doc(&self, doc_index: usize) -> Result<Vec<OfdItem<Attachment>>> {
+        self.0.borrow_mut().attachments_for_doc(doc_index)
+    }
+
+    pub fn extensions_for_doc(
+        &self,
+        doc_index: usize,
+    ) -> Result<Option<OfdItem<ExtensionXmlFile>>> {
+        self.0.borrow_mut().extensions_for_doc(doc_index)
+    }
+
+    pub fn custom_tags_for_doc(
+        &self,
+        doc_index: usize,
+    ) -> Result<Option<OfdItem<CustomTagsXmlFile>>> {
+        self.0.borrow_mut().custom_tags_for_doc(doc_index)
+    }
+
+    pub fn custom_tag_files_for_doc(&self, doc_index: usize) -> Result<Vec<OfdItem<TagFile>>> {
+        self.0.borrow_mut().custom_tag_files_for_doc(doc_index)
+    }
+
+    /// find an object on a page by its id, `page_id` is the `ID` of the page
+    pub fn page_object_by_id(
+        &self,
+        doc_index: usize,
+        page_id: StRefId,
+        object_id: StRefId,
+    ) -> Result<Option<VtGraphicUnit>> {
+        self.0
+            .borrow_mut()
+            .page_object_by_id(doc_index, page_id, object_id)
+    }
+
+    /// all the ids of a document, built on first use
+    pub fn id_index(&self, doc_index: usize) -> Result<Rc<IdIndex>> {
+        self.0.borrow_mut().id_index(doc_index)
+    }
+
+    /// index of a page by its `ID`
+    pub fn page_index_by_id(&self, doc_index: usize, page_id: StRefId) -> Result<Option<usize>> {
+        Ok(self.id_index(doc_index)?.page_index_by_id(page_id))
+    }
+
+    /// find an object on any page, template or annotation by its id
+    pub fn object_by_id(
+        &self,
+        doc_index: usize,
+        object_id: StRefId,
+    ) -> Result<Option<VtGraphicUnit>> {
+        self.0.borrow_mut().object_by_id(doc_index, object_id)
+    }
+
+    /// the outline tree of a document flattened in depth first order,
+    /// with destinations resolved to page indices
+    pub fn outlines_for_doc(&self, doc_index: usize) -> Result<Vec<OutlineEntry>> {
+        let doc = self.document_by_index(doc_index)?;
+        Ok(outline::flatten_outlines(&doc))
+    }
+
+    pub fn bookmarks_for_doc(&self, doc_index: usize) -> Result<Vec<BookmarkEntry>> {
+        let doc = self.document_by_index(doc_index)?;
+        Ok(outline::bookmarks(&doc))
+    }
+
+    /// text of the text objects a tag element points to
+    pub fn tagged_text(&self, doc_index: usize, element: &TagElement) -> Result<String> {
+        self.0.borrow_mut().tagged_text(doc_index, element)
+    }
+
     pub fn signatures_for_doc(
         &self,
         doc_index: usize,
@@ -123,132 +251,409 @@ impl<R: Read + Seek> Ofd<R> {
     pub fn signatures_for_page(&self, p0: usize, p1: usize) -> Result<Option<Stamps>> {
         self.0.borrow_mut().signature_for_page(p0, p1)
     }
-}
 
-pub type Stamps = Vec<(OfdItem<SignatureXmlFile>, StampAnnot)>;
+    /// read malformed packages as far as possible
+    ///
+    /// missing or unparsable parts are defaulted or skipped,
+    /// and recorded as [Recovery]s. the mode is shared by all the clones
+    /// of this [Ofd], items already read are not affected.
+    pub fn set_lenient(&self, lenient: bool) {
+        self.0.borrow_mut().lenient = lenient;
+    }
 
-impl<R> RawOfd<R> {
-    const OFD_ENTRY: &'static str = "OFD.xml";
+    pub fn is_lenient(&self) -> bool {
+        self.0.borrow().lenient
+    }
 
-    /// get an item from cache
-    fn get_cache<T: 'static, S>(&mut self, path: S) -> Option<&T>
-    where
-        S: Into<String>,
-    {
-        let tid = TypeId::of::<T>();
-        let path = path.into();
-        let key = CacheKey { path, tid };
-        self.cache.get(&key).and_then(|x| x.downcast_ref::<T>())
+    /// what has been recovered so far in lenient mode
+    pub fn recoveries(&self) -> Vec<Recovery> {
+        self.0.borrow().recoveries.clone()
+    }
+
+    /// limit what is read from the package, for untrusted packages
+    ///
+    /// the item count is checked right away, the other limits when items are read.
+    /// the limits are shared by all the clones of this [Ofd].
+    pub fn set_limits(&self, limits: Limits) -> Result<()> {
+        let mut raw = self.0.borrow_mut();
+        limits.check_items(raw.item_names().len())?;
+        raw.limits = limits;
+        Ok(())
     }
 
-    /// set an item into cache
-    fn set_cache<T: 'static, S>(&mut self, path: S, value: T)
+    pub fn limits(&self) -> Limits {
+        self.0.borrow().limits
+    }
+
+    /// memory budget of the parsed items, in bytes of the xml they are parsed from
+    ///
+    /// the least recently used items are dropped when over the budget,
+    /// 0 turns off caching. the default is 64 MiB.
+    pub fn set_cache_budget(&self, bytes: usize) {
+        self.0.borrow_mut().cache.set_budget(bytes);
+    }
+
+    /// bytes of the xml the cached items are parsed from
+    pub fn cache_size(&self) -> usize {
+        self.0.borrow().cache.used()
+    }
+
+    pub fn clear_cache(&self) {
+        self.0.borrow_mut().cache.clear();
+    }
+
+    /// the repaired xml items, by item name
+    pub(crate) fn repaired_items(&self) -> Vec<(String, Vec<u8>)> {
+        let raw = self.0.borrow();
+        raw.repaired
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    /// `Document.xml` located by scanning, by doc index
+    pub(crate) fn located_roots(&self) -> Vec<(usize, RelativePathBuf)> {
+        let raw = self.0.borrow();
+        raw.located_roots
+            .iter()
+            .map(|(k, v)| (*k, v.clone()))
+            .collect()
+    }
+
+    /// parse an xml item by its path in package
+    pub(crate) fn xml_by_path<T>(&self, path: impl Into<String>) -> Result<Rc<T>>
     where
-        S: Into<String>,
+        T: 'static + DeserializeOwned,
     {
-        let tid = TypeId::of::<T>();
-        let p = path.into();
-        let key = CacheKey { path: p, tid };
-        self.cache.insert(key, Box::new(value));
+        self.0.borrow_mut().cache_or(path)
+    }
+
+    /// copy an item into `writer` without decompressing it
+    pub(crate) fn raw_copy_to<W: Write + Seek>(
+        &self,
+        path: &str,
+        writer: &mut OfdWriter<W>,
+    ) -> Result<()> {
+        self.0.borrow_mut().raw_copy_to(path, writer)
     }
 }
 
-impl<RD: Read + Seek> RawOfd<RD> {
-    fn read_item<T, R>(reader: R) -> Result<T>
+pub type Stamps = Vec<(OfdItem<SignatureXmlFile>, StampAnnot)>;
+
+pub type DocVersions = Vec<(Version, OfdItem<VersionXmlFile>)>;
+
+impl<S> RawOfd<S> {
+    pub(crate) const OFD_ENTRY: &'static str = "OFD.xml";
+
+    pub(crate) fn new(storage: S) -> Self {
+        Self {
+            storage,
+            cache: ItemCache::new(DEFAULT_CACHE_BUDGET),
+            version_roots: HashMap::new(),
+            version_files: HashMap::new(),
+            lenient: false,
+            recoveries: vec![],
+            repaired: BTreeMap::new(),
+            located_roots: BTreeMap::new(),
+            limits: Limits::default(),
+            bytes_read: Arc::default(),
+        }
+    }
+
+    /// a view of a shared package, counting the bytes read with the other views
+    pub(crate) fn with_limits(mut self, limits: Limits, bytes_read: Arc<AtomicU64>) -> Self {
+        self.limits = limits;
+        self.bytes_read = bytes_read;
+        self
+    }
+}
+
+impl<S: Storage> RawOfd<S> {
+    /// parse an xml item, `path` is for error reporting
+    pub(crate) fn read_item<T, R>(path: &str, reader: R) -> Result<T>
     where
         T: DeserializeOwned,
         R: BufRead,
     {
-        let mut reader = BufReader::new(reader);
-        let buf = reader.fill_buf()?;
-
-        // UTF-8 BOM
-        // handle u+FEFF in utf-8 file
-        // just skip this three bytes
-        if buf.starts_with(&[0xef_u8, 0xbb, 0xbf]) {
-            reader.consume(3);
-        }
-        let root = Element::from_reader_with_prefixes(reader, Self::OFD_ENTRY.to_string())?;
-        let res: T = xdom::de::from_ele(&root)?;
-        Ok(res)
+        let root = read_element(reader)?;
+        parse_element(path, &root)
     }
 
-    fn open<P>(&mut self, path: P) -> Result<ZipFile>
+    fn open<P>(&mut self, path: P) -> Result<Box<dyn Read + '_>>
     where
         P: AsRef<str> + Into<String>,
     {
         let path = path.as_ref();
-        debug!("opening item in zip: {}", path);
-        let file = self
-            .zip_archive
-            .by_name(path)
-            .map_err(|e| Error::OpenZipError(e, path.into()))?;
-        Ok(file)
+        debug!("opening item in package: {}", path);
+        self.storage.open(path)
     }
 
     /// getting from cache or parse xml from file
-    fn cache_or<R, P>(&mut self, path: P) -> Result<R>
+    fn cache_or<R, P>(&mut self, path: P) -> Result<Rc<R>>
     where
-        R: Clone + 'static + DeserializeOwned,
+        R: 'static + DeserializeOwned,
         P: Into<String>,
     {
-        let p = path.into();
-        if let Some(cache) = self.get_cache::<R, _>(p.clone()) {
-            Ok(cache.clone())
+        self.cache_rc::<R>(&path.into())
+    }
+
+    /// getting from cache or parse xml from file, shared with the cache
+    fn cache_rc<R>(&mut self, path: &str) -> Result<Rc<R>>
+    where
+        R: 'static + DeserializeOwned,
+    {
+        let path = &self.substitute(path);
+        if let Some(xml) = self.cache.get::<R>(path) {
+            return Ok(xml);
+        }
+        let (xml, size): (R, usize) = if self.lenient {
+            self.read_lenient(path)?
         } else {
-            let inner = self.open(&p)?;
-            let reader = BufReader::new(inner);
-            let xml: R = RawOfd::<RD>::read_item(reader)?;
-            self.set_cache(p, xml.clone());
-            Ok(xml)
+            let (root, size) = self.read_root(path)?;
+            (parse_element(path, &root)?, size)
+        };
+        let xml = Rc::new(xml);
+        self.cache.insert(path, xml.clone(), size);
+        Ok(xml)
+    }
+
+    /// read an item, recovering what it can from a malformed or missing one
+    ///
+    /// returns the item and the size of the xml read
+    fn read_lenient<R>(&mut self, path: &str) -> Result<(R, usize)>
+    where
+        R: 'static + DeserializeOwned,
+    {
+        let read = self.read_root(path);
+        let (mut root, size, mut repaired) = match read {
+            Ok((root, size)) => (root, size, false),
+            Err(e) if e.is_not_found() => {
+                let root = repair::empty_root::<R>().ok_or(e)?;
+                self.recover(path, RecoveryKind::MissingItem);
+                (root, 0, true)
+            }
+            Err(e) => return Err(e),
+        };
+        if xdom::de::from_ele::<R>(&root).is_err() {
+            for kind in repair::repair::<R>(&mut root) {
+                self.recover(path, kind);
+                repaired = true;
+            }
+        }
+        let xml = parse_element(path, &root)?;
+        if repaired {
+            let mut bytes = vec![];
+            root.write_to_decl(&mut bytes)?;
+            self.repaired.insert(path.into(), bytes);
+        }
+        Ok((xml, size))
+    }
+
+    /// the item read in place of `path`, a file of a selected version or `path` itself
+    fn substitute(&self, path: &str) -> String {
+        self.version_files
+            .values()
+            .find_map(|files| files.get(RelativePath::new(path)))
+            .map_or(path, |p| p.as_str())
+            .to_string()
+    }
+
+    /// read an item within the limits
+    fn read_into<W: Write>(&mut self, path: &str, writer: &mut W) -> Result<u64> {
+        let limits = self.limits;
+        let compressed = limits
+            .max_compression_ratio
+            .and_then(|_| self.storage.compressed_size(path));
+        let bytes_read = self.bytes_read.clone();
+        let mut file = self.open(path)?;
+        let mut buf = [0; 8192];
+        let mut read = 0_u64;
+        loop {
+            let n = match file.read(&mut buf) {
+                Ok(0) => return Ok(read),
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            read += n as u64;
+            let total = bytes_read.fetch_add(n as u64, Ordering::Relaxed) + n as u64;
+            limits.check_read(path, read, compressed, total)?;
+            writer.write_all(&buf[..n])?;
+        }
+    }
+
+    /// read the root element of an xml item within the limits,
+    /// with the size of the xml
+    fn read_root(&mut self, path: &str) -> Result<(Element, usize)> {
+        let mut bytes = vec![];
+        self.read_into(path, &mut bytes)?;
+        self.limits.check_xml(path, &bytes)?;
+        Ok((read_element(bytes.as_slice())?, bytes.len()))
+    }
+
+    fn recover(&mut self, path: &str, kind: RecoveryKind) {
+        let recovery = Recovery {
+            path: path.into(),
+            kind,
+        };
+        if !self.recoveries.contains(&recovery) {
+            self.recoveries.push(recovery);
         }
     }
 
+    /// find the `Document.xml` of a document without a valid `DocRoot`,
+    /// `Doc_N/Document.xml` is preferred
+    fn locate_doc_root(&mut self, doc_index: usize) -> Result<RelativePathBuf> {
+        if let Some(path) = self.located_roots.get(&doc_index) {
+            return Ok(path.clone());
+        }
+        let mut candidates = self
+            .item_names()
+            .into_iter()
+            .filter(|n| n == "Document.xml" || n.ends_with("/Document.xml"))
+            .collect::<Vec<_>>();
+        candidates.sort();
+        let preferred = format!("Doc_{doc_index}/Document.xml");
+        let path = if candidates.contains(&preferred) {
+            preferred
+        } else {
+            candidates
+                .into_iter()
+                .nth(doc_index)
+                .ok_or(Error::NoSuchDocument)?
+        };
+        let path = RelativePathBuf::from(path);
+        self.recover(path.as_str(), RecoveryKind::DocRootLocated { doc_index });
+        self.located_roots.insert(doc_index, path.clone());
+        Ok(path)
+    }
+
     /// get entry file of ofd
     pub fn entry(&mut self) -> Result<OfdItem<OfdXmlFile>> {
-        let xml = self.cache_or(RawOfd::<RD>::OFD_ENTRY)?;
+        let xml = self.cache_or(RawOfd::<S>::OFD_ENTRY)?;
 
         Ok(OfdItem {
-            path: RawOfd::<RD>::OFD_ENTRY.into(),
+            path: RawOfd::<S>::OFD_ENTRY.into(),
             content: xml,
         })
     }
 
     /// get a reader
-    pub fn _reader<P>(&mut self, path: P) -> Result<BufReader<ZipFile>>
+    pub fn _reader<P>(&mut self, path: P) -> Result<BufReader<Box<dyn Read + '_>>>
     where
         P: AsRef<str> + Into<String>,
     {
-        let file = self.zip_archive.by_name(path.as_ref())?;
+        let file = self.open(path)?;
         Ok(BufReader::new(file))
     }
 
+    fn raw_copy_to<W: Write + Seek>(
+        &mut self,
+        path: &str,
+        writer: &mut OfdWriter<W>,
+    ) -> Result<()> {
+        self.storage.copy_to(path, writer)
+    }
+
+    /// copy an item into `writer` without loading it into memory
+    pub fn copy_item_to<P, W>(&mut self, path: P, writer: &mut W) -> Result<u64>
+    where
+        P: AsRef<str> + Into<String>,
+        W: Write,
+    {
+        let path = self.substitute(path.as_ref());
+        self.read_into(&path, writer)
+    }
+
     /// get as bytes
     pub fn bytes<P>(&mut self, path: P) -> Result<Vec<u8>>
     where
         P: AsRef<str> + Into<String>,
     {
-        let mut file = self.zip_archive.by_name(path.as_ref())?;
+        let path = self.substitute(path.as_ref());
         let mut buf = Vec::new();
-        file.read_to_end(&mut buf)?;
+        self.read_into(&path, &mut buf)?;
         Ok(buf)
     }
 
     pub fn document_by_index(&mut self, doc_index: usize) -> Result<OfdItem<DocumentXmlFile>> {
-        // get path
+        if let Some(path) = self.version_roots.get(&doc_index).cloned() {
+            let xml = self.cache_or(path.clone())?;
+            return Ok(OfdItem { path, content: xml });
+        }
+        let path = self.entry_doc_root(doc_index)?;
+
+        // cache or read
+        let xml = self.cache_or(path.clone())?;
+
+        Ok(OfdItem { path, content: xml })
+    }
+
+    /// `DocRoot` of a document in `OFD.xml`
+    fn entry_doc_root(&mut self, doc_index: usize) -> Result<RelativePathBuf> {
         let entry = self.entry()?;
         let doc_body = entry
             .content
             .doc_body
             .get(doc_index)
             .ok_or(Error::NoSuchDocument)?;
-        let path = doc_body.doc_root.as_ref().ok_or(Error::NoSuchDocument)?;
-        let path = entry.resolve(path);
+        let path = match doc_body.doc_root.as_ref().map(|p| entry.resolve(p)) {
+            Some(Ok(path)) if !self.lenient || self.storage.contains(path.as_str()) => path,
+            _ if self.lenient => self.locate_doc_root(doc_index)?,
+            Some(Err(e)) => return Err(e),
+            _ => return Err(Error::NoSuchDocument),
+        };
+        Ok(path)
+    }
 
-        // cache or read
-        let xml = self.cache_or(path.clone())?;
+    pub fn versions_for_doc(&mut self, doc_index: usize) -> Result<DocVersions> {
+        let entry = self.entry()?;
+        let doc_body = entry
+            .content
+            .doc_body
+            .get(doc_index)
+            .ok_or(Error::NoSuchDocument)?;
+        let mut versions = doc_body
+            .versions
+            .iter()
+            .flat_map(|v| v.version.iter())
+            .map(|v| -> Result<(Version, OfdItem<VersionXmlFile>)> {
+                let path = entry.resolve(&v.base_loc)?;
+                let xml = self.cache_or(path.clone())?;
+                Ok((v.clone(), OfdItem { path, content: xml }))
+            })
+            .collect::<Result<DocVersions>>()?;
+        versions.sort_by_key(|(v, _)| v.index);
+        Ok(versions)
+    }
 
-        Ok(OfdItem { path, content: xml })
+    fn select_version(&mut self, doc_index: usize, version_id: Option<&str>) -> Result<()> {
+        if let Some(id) = version_id {
+            let (_, file) = self
+                .versions_for_doc(doc_index)?
+                .into_iter()
+                .find(|(v, _)| v.id == id)
+                .ok_or(Error::NoSuchVersion)?;
+            // a file under the folder of the version stands for
+            // the item at the same place under the folder of the document
+            let doc_path = self.entry_doc_root(doc_index)?;
+            let doc_dir = doc_path.parent().unwrap_or(RelativePath::new(""));
+            let version_dir = file.path().parent().unwrap_or(RelativePath::new(""));
+            let files = file
+                .files()?
+                .into_iter()
+                .filter_map(|(_, path)| {
+                    let item = doc_dir.join(path.strip_prefix(version_dir).ok()?);
+                    Some((item, path))
+                })
+                .collect();
+            self.version_roots.insert(doc_index, file.doc_root()?);
+            self.version_files.insert(doc_index, files);
+        } else {
+            self.version_roots.remove(&doc_index);
+            self.version_files.remove(&doc_index);
+        }
+        Ok(())
     }
 
     #[deprecated]
@@ -266,15 +671,14 @@ impl<RD: Read + Seek> RawOfd<RD> {
             .ok_or(Error::NoSuchTemplate)?;
         let tpl_el = tpls.get(template_index).ok_or(Error::NoSuchTemplate)?;
         let tpl_path = &tpl_el.base_loc;
-        let tpl_path = doc.resolve(tpl_path);
-        let inner = self.open(tpl_path.to_string())?;
-        let reader = BufReader::new(inner);
-        let xml: PageXmlFile = RawOfd::<RD>::read_item(reader)?;
+        let tpl_path = doc.resolve(tpl_path)?;
+        let (root, _) = self.read_root(tpl_path.as_str())?;
+        let xml: PageXmlFile = parse_element(tpl_path.as_str(), &root)?;
         // let cont = &*self;
         Ok(OfdItem {
             // container: self,
             path: tpl_path,
-            content: xml,
+            content: Rc::new(xml),
         })
     }
 
@@ -297,7 +701,7 @@ impl<RD: Read + Seek> RawOfd<RD> {
             .find(|i| i.id == template_id)
             .ok_or(Error::NoSuchTemplate)?;
         let tpl_path = &tpl_el.base_loc;
-        let path = doc.resolve(tpl_path);
+        let path = doc.resolve(tpl_path)?;
 
         let xml = self.cache_or(path.clone())?;
 
@@ -313,7 +717,7 @@ impl<RD: Read + Seek> RawOfd<RD> {
         let pages = &doc.content.pages.page;
         let tpl_el = pages.get(page_index).ok_or(Error::NoSuchTemplate)?;
         let tpl_path = &tpl_el.base_loc;
-        let path = doc.resolve(tpl_path);
+        let path = doc.resolve(tpl_path)?;
 
         // cache or read
         let xml = self.cache_or(path.clone())?;
@@ -354,12 +758,12 @@ impl<RD: Read + Seek> RawOfd<RD> {
         let page = self.page_by_index(doc_index, page_index)?;
         let page_res_locs = &page.content.page_res;
         let page_res = self._load_res(&page, page_res_locs)?;
-        Ok(Resources {
-            default_cs: doc.content.common_data.default_cs,
-            public_resource: pub_res,
-            document_resource: doc_res,
-            page_resource: page_res,
-        })
+        Ok(Resources::new(
+            doc.content.common_data.default_cs,
+            pub_res,
+            doc_res,
+            page_res,
+        ))
     }
 
     fn _load_res<T>(
@@ -374,11 +778,8 @@ impl<RD: Read + Seek> RawOfd<RD> {
                 let r = paths
                     .iter()
                     .map(|p| -> Result<OfdItem<ResourceXmlFile>> {
-                        let rp = parent.resolve(p);
-                        let file = self.open(rp.to_string())?;
-                        let reader = BufReader::new(file);
-
-                        let xml: ResourceXmlFile = RawOfd::<RD>::read_item(reader)?;
+                        let rp = parent.resolve(p)?;
+                        let xml = self.cache_or::<ResourceXmlFile, _>(rp.as_str())?;
 
                         Ok(OfdItem {
                             path: rp,
@@ -394,8 +795,8 @@ impl<RD: Read + Seek> RawOfd<RD> {
         Ok(res)
     }
 
-    pub fn item_names(&self) -> impl Iterator<Item = &str> {
-        self.zip_archive.file_names()
+    pub fn item_names(&self) -> Vec<String> {
+        self.storage.item_names()
     }
 
     /// load resources for doc
@@ -417,20 +818,15 @@ impl<RD: Read + Seek> RawOfd<RD> {
         assert!(page_index < vec.len(), "page_index out of range");
         let page_id = vec[page_index].id;
         if let Some(loc) = &doc.annotations {
-            let path = doc.resolve(loc);
-            let file = self.open(path.to_string())?;
-            let reader = BufReader::new(file);
-            let xml: AnnotationsXmlFile = RawOfd::<RD>::read_item(reader)?;
+            let path = doc.resolve(loc)?;
+            let xml = self.cache_rc::<AnnotationsXmlFile>(path.as_str())?;
             if let Some(pages) = &xml.page {
                 let anno_vec = pages
                     .iter()
                     .filter(|p| p.page_id == page_id)
                     .map(|f| {
-                        let p = inner_resolve(&path, &f.file_loc);
-                        let file = self.open(p.to_string())?;
-
-                        let reader = BufReader::new(file);
-                        let xml: AnnotationXmlFile = RawOfd::<RD>::read_item(reader)?;
+                        let p = inner_resolve(&path, &f.file_loc)?;
+                        let xml = self.cache_or::<AnnotationXmlFile, _>(p.as_str())?;
                         Ok(OfdItem {
                             path: p,
                             content: xml,
@@ -446,6 +842,169 @@ impl<RD: Read + Seek> RawOfd<RD> {
         }
     }
 
+    /// read attachments.xml
+    ///
+    /// the path of each returned item is the resolved `FileLoc` of the attachment
+    pub fn attachments_for_doc(&mut self, doc_index: usize) -> Result<Vec<OfdItem<Attachment>>> {
+        let doc = self.document_by_index(doc_index)?;
+        if let Some(loc) = &doc.attachments {
+            let path = doc.resolve(loc)?;
+            let xml = self.cache_or::<AttachmentsXmlFile, _>(path.clone())?;
+            let attachments = xml
+                .attachments
+                .iter()
+                .flatten()
+                .map(|a| {
+                    Ok(OfdItem {
+                        path: inner_resolve(&path, &a.file_loc)?,
+                        content: Rc::new(a.clone()),
+                    })
+                })
+                .collect::<Result<_>>()?;
+            Ok(attachments)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// read Extensions.xml
+    pub fn extensions_for_doc(
+        &mut self,
+        doc_index: usize,
+    ) -> Result<Option<OfdItem<ExtensionXmlFile>>> {
+        let doc = self.document_by_index(doc_index)?;
+        if let Some(loc) = &doc.extensions {
+            let path = doc.resolve(loc)?;
+            let xml = self.cache_or::<ExtensionXmlFile, _>(path.clone())?;
+            Ok(Some(OfdItem { path, content: xml }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// read CustomTags.xml
+    pub fn custom_tags_for_doc(
+        &mut self,
+        doc_index: usize,
+    ) -> Result<Option<OfdItem<CustomTagsXmlFile>>> {
+        let doc = self.document_by_index(doc_index)?;
+        if let Some(loc) = &doc.custom_tags {
+            let path = doc.resolve(loc)?;
+            let xml = self.cache_or::<CustomTagsXmlFile, _>(path.clone())?;
+            Ok(Some(OfdItem { path, content: xml }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// read each custom tag file
+    ///
+    /// the path of each returned item is the resolved `FileLoc` of the tag
+    pub fn custom_tag_files_for_doc(&mut self, doc_index: usize) -> Result<Vec<OfdItem<TagFile>>> {
+        let tags = match self.custom_tags_for_doc(doc_index)? {
+            Some(tags) => tags,
+            None => return Ok(Vec::new()),
+        };
+        tags.custom_tags
+            .iter()
+            .flatten()
+            .map(|tag| -> Result<OfdItem<TagFile>> {
+                let path = tags.resolve(&tag.file_loc)?;
+                let mut bytes = vec![];
+                self.read_into(path.as_str(), &mut bytes)?;
+                self.limits.check_xml(path.as_str(), &bytes)?;
+                let root = read_tag_file(bytes.as_slice())?;
+                Ok(OfdItem {
+                    path,
+                    content: Rc::new(TagFile {
+                        type_id: tag.type_id.clone(),
+                        namespace: tag.namespace.clone(),
+                        schema_loc: tag
+                            .schema_loc
+                            .as_ref()
+                            .map(|p| tags.resolve(p))
+                            .transpose()?,
+                        root,
+                    }),
+                })
+            })
+            .collect()
+    }
+
+    pub fn page_object_by_id(
+        &mut self,
+        doc_index: usize,
+        page_id: StRefId,
+        object_id: StRefId,
+    ) -> Result<Option<VtGraphicUnit>> {
+        let page_index = self
+            .id_index(doc_index)?
+            .page_index_by_id(page_id)
+            .ok_or(Error::NoSuchPage)?;
+        let page = self.page_by_index(doc_index, page_index)?;
+        let object = page
+            .content
+            .content
+            .iter()
+            .flat_map(|c| c.layer.iter())
+            .find_map(|l| find_object(l.objects.as_deref(), object_id));
+        Ok(object.cloned())
+    }
+
+    /// the index is cached by the path of `Document.xml`,
+    /// so each selected version gets its own
+    pub fn id_index(&mut self, doc_index: usize) -> Result<Rc<IdIndex>> {
+        let doc = self.document_by_index(doc_index)?;
+        if let Some(index) = self.cache.get::<IdIndex>(doc.path.as_str()) {
+            return Ok(index);
+        }
+        let index = index_document(doc.path(), &doc, self)?.index;
+        let size = index.len() * size_of::<(StRefId, IdTarget)>();
+        let index = Rc::new(index);
+        self.cache.insert(doc.path.as_str(), index.clone(), size);
+        Ok(index)
+    }
+
+    pub fn object_by_id(
+        &mut self,
+        doc_index: usize,
+        object_id: StRefId,
+    ) -> Result<Option<VtGraphicUnit>> {
+        let index = self.id_index(doc_index)?;
+        let object = match index.get(object_id) {
+            Some(IdTarget::Object { path }) => {
+                let xml = self.cache_rc::<PageXmlFile>(path.as_str())?;
+                xml.content
+                    .iter()
+                    .flat_map(|c| c.layer.iter())
+                    .find_map(|l| find_object(l.objects.as_deref(), object_id))
+                    .cloned()
+            }
+            Some(IdTarget::AppearanceObject { path }) => {
+                let xml = self.cache_rc::<AnnotationXmlFile>(path.as_str())?;
+                xml.annot
+                    .iter()
+                    .find_map(|a| find_object(a.appearance.objects.as_deref(), object_id))
+                    .cloned()
+            }
+            _ => None,
+        };
+        Ok(object)
+    }
+
+    pub fn tagged_text(&mut self, doc_index: usize, element: &TagElement) -> Result<String> {
+        let mut text = String::new();
+        for r in element.object_refs.iter() {
+            let object = self.page_object_by_id(doc_index, r.page_ref, r.object_id)?;
+            if let Some(VtGraphicUnit::TextObject(t)) = object {
+                for tv in t.text_vals.iter() {
+                    text.push_str(&tv.text_code.val);
+                }
+            }
+        }
+        Ok(text)
+    }
+
     /// read signatures.xml
     fn signatures_for_doc(
         &mut self,
@@ -454,7 +1013,7 @@ impl<RD: Read + Seek> RawOfd<RD> {
         let entry = self.entry()?;
         let xml = &entry.content.doc_body[doc_index];
         if let Some(p) = &xml.signatures {
-            let path = entry.resolve(p);
+            let path = entry.resolve(p)?;
             let e = self.cache_or::<SignaturesXmlFile, _>(path.clone())?;
             Ok(Some(OfdItem { path, content: e }))
         } else {
@@ -481,7 +1040,7 @@ impl<RD: Read + Seek> RawOfd<RD> {
         let s = sigs
             .iter()
             .map(|sig| -> Result<OfdItem<SignatureXmlFile>> {
-                let path = sigs_file.resolve(&sig.base_loc);
+                let path = sigs_file.resolve(&sig.base_loc)?;
                 let f = self.cache_or::<SignatureXmlFile, _>(path.clone())?;
                 Ok(OfdItem { path, content: f })
             })
@@ -497,7 +1056,7 @@ impl<RD: Read + Seek> RawOfd<RD> {
         let sigs = self.signature_for_doc(doc_index)?;
 
         let doc = self.document_by_index(doc_index)?;
-        let page = doc.pages.page.get(page_index).unwrap();
+        let page = doc.pages.page.get(page_index).ok_or(Error::NoSuchPage)?;
         let page_id = page.id;
 
         if sigs.is_none() {
@@ -519,11 +1078,30 @@ impl<RD: Read + Seek> RawOfd<RD> {
     }
 }
 
+impl<S: Storage> IndexLoader for RawOfd<S> {
+    fn locate(
+        &mut self,
+        parent: &RelativePathBuf,
+        _name: &'static str,
+        loc: &StLoc,
+    ) -> Result<Option<RelativePathBuf>> {
+        inner_resolve(parent, loc).map(Some)
+    }
+
+    fn load<T>(&mut self, path: &RelativePathBuf) -> Result<Option<Rc<T>>>
+    where
+        T: 'static + DeserializeOwned,
+    {
+        self.cache_rc(path.as_str()).map(Some)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OfdItem<T> {
     // container: &'a mut Container,
     path: RelativePathBuf,
-    pub content: T,
+    /// shared with the cache, clone it to edit
+    pub content: Rc<T>,
 }
 impl<T> Deref for OfdItem<T> {
     type Target = T;
@@ -533,167 +1111,329 @@ impl<T> Deref for OfdItem<T> {
     }
 }
 /// this holds some resource for render
+///
+/// resources are looked up by id, page resources first,
+/// then document resources, then public resources.
 pub struct Resources {
     pub default_cs: Option<StRefId>,
-    public_resource: Option<Vec<OfdItem<ResourceXmlFile>>>,
-    document_resource: Option<Vec<OfdItem<ResourceXmlFile>>>,
-    page_resource: Option<Vec<OfdItem<ResourceXmlFile>>>,
+    // page, document, then public resource files
+    files: Vec<OfdItem<ResourceXmlFile>>,
+    index: HashMap<(ResourceKind, StRefId), ResourceSlot>,
 }
 
-pub struct ResourceIter<'a> {
-    res_flat: Vec<&'a OfdItem<ResourceXmlFile>>,
-    // res: &'a Resources,
-    idx: usize,
+/// where a resource is, indices of the file, the resource group and the item
+#[derive(Debug, Clone, Copy)]
+struct ResourceSlot {
+    file: usize,
+    group: usize,
+    item: usize,
 }
-impl<'a> ResourceIter<'a> {
-    fn new(res: &'a Resources) -> Self {
-        let res_flat = [
-            &res.page_resource,
-            &res.document_resource,
-            &res.public_resource,
-        ]
-        .iter()
-        .filter_map(|e| e.as_ref())
-        .flat_map(|e| e.iter())
-        .collect::<Vec<&OfdItem<ResourceXmlFile>>>();
-        Self {
-            res_flat,
-            // res,
-            idx: 0,
-        }
-    }
+
+pub struct ResourceIter<'a> {
+    inner: std::slice::Iter<'a, OfdItem<ResourceXmlFile>>,
 }
+
 impl<'a> Iterator for ResourceIter<'a> {
     type Item = &'a OfdItem<ResourceXmlFile>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let res = if self.idx < self.res_flat.len() {
-            Some(self.res_flat[self.idx])
-        } else {
-            None
-        };
-        self.idx += 1;
-        res
+        self.inner.next()
     }
 }
 
 impl Resources {
-    pub fn iter(&self) -> ResourceIter {
-        ResourceIter::new(self)
+    fn new(
+        default_cs: Option<StRefId>,
+        public_resource: Option<Vec<OfdItem<ResourceXmlFile>>>,
+        document_resource: Option<Vec<OfdItem<ResourceXmlFile>>>,
+        page_resource: Option<Vec<OfdItem<ResourceXmlFile>>>,
+    ) -> Self {
+        let files = [page_resource, document_resource, public_resource]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect::<Vec<_>>();
+        let mut index = HashMap::new();
+        for (file, f) in files.iter().enumerate() {
+            for (group, r) in f.content.resources.iter().flatten().enumerate() {
+                let ids: Vec<StRefId> = match r {
+                    Resource::ColorSpaces(r) => r.color_spaces.iter().map(|r| r.id).collect(),
+                    Resource::DrawParams(r) => r.draw_params.iter().map(|r| r.id).collect(),
+                    Resource::Fonts(r) => r.fonts.iter().map(|r| r.id).collect(),
+                    Resource::MultiMedias(r) => r.multi_medias.iter().map(|r| r.id).collect(),
+                    Resource::CompositeGraphicUnits(r) => {
+                        r.composite_graphic_units.iter().map(|r| r.id).collect()
+                    }
+                };
+                let kind = resource_kind(r);
+                for (item, id) in ids.into_iter().enumerate() {
+                    // the first one wins
+                    index
+                        .entry((kind, id))
+                        .or_insert(ResourceSlot { file, group, item });
+                }
+            }
+        }
+        Self {
+            default_cs,
+            files,
+            index,
+        }
+    }
+
+    /// resource files, page resources first
+    pub fn iter(&self) -> ResourceIter<'_> {
+        ResourceIter {
+            inner: self.files.iter(),
+        }
     }
+
+    /// the file and the resource group holding a resource, with the index in the group
+    fn lookup(
+        &self,
+        kind: ResourceKind,
+        id: StRefId,
+    ) -> Option<(&OfdItem<ResourceXmlFile>, &Resource, usize)> {
+        let slot = self.index.get(&(kind, id))?;
+        let file = &self.files[slot.file];
+        let group = file.content.resources.as_ref()?.get(slot.group)?;
+        Some((file, group, slot.item))
+    }
+
     pub fn get_color_space_by_id(&self, color_space_id: StRefId) -> Option<&ColorSpace> {
-        let cs = self
-            .iter()
-            .filter_map(|f| f.content.resources.as_ref())
-            .flat_map(|r| r.iter())
-            .filter_map(|r| match r {
-                Resource::ColorSpaces(css) => Some(css),
-                _ => None,
-            })
-            .flat_map(|css| css.color_spaces.iter())
-            .find(|cs| cs.id == color_space_id);
-        cs
+        match self.lookup(ResourceKind::ColorSpace, color_space_id)? {
+            (_, Resource::ColorSpaces(r), i) => r.color_spaces.get(i),
+            _ => None,
+        }
     }
 
     pub fn get_draw_param_by_id(&self, draw_param_id: StRefId) -> Option<DrawParam> {
-        let dp = self
-            .iter()
-            .filter_map(|f| f.content.resources.as_ref())
-            .flat_map(|r| r.iter())
-            .filter_map(|r| match r {
-                Resource::DrawParams(dp) => Some(dp),
-                _ => None,
-            })
-            .flat_map(|dps| dps.draw_params.iter())
-            .find(|dp| dp.id == draw_param_id);
-        dp.cloned()
+        match self.lookup(ResourceKind::DrawParam, draw_param_id)? {
+            (_, Resource::DrawParams(r), i) => r.draw_params.get(i).cloned(),
+            _ => None,
+        }
     }
+
     pub fn get_font_by_id(&self, font_id: StRefId) -> Option<(&OfdItem<ResourceXmlFile>, &Font)> {
-        let font = self
-            .flat()
-            .into_iter()
-            .filter_map(|(f, r)| match r {
-                Resource::Fonts(fts) => Some((f, fts)),
-                _ => None,
-            })
-            .flat_map(|(fi, f)| f.fonts.iter().map(move |f| (fi, f)))
-            .find(|(_, f)| f.id == font_id);
-        font
+        match self.lookup(ResourceKind::Font, font_id)? {
+            (f, Resource::Fonts(r), i) => Some((f, r.fonts.get(i)?)),
+            _ => None,
+        }
     }
 
-    fn flat(&self) -> Vec<(&OfdItem<ResourceXmlFile>, &Resource)> {
-        let x = self
-            .iter()
-            .filter_map(|f| f.content.resources.as_ref().map(|r| (f, r)))
-            .flat_map(|(f, v)| v.iter().map(move |v| (f, v)))
-            .collect::<Vec<_>>();
-        x
+    /// any multimedia, image, audio or video
+    pub fn get_multi_media_by_id(
+        &self,
+        multi_media_id: StRefId,
+    ) -> Option<(&OfdItem<ResourceXmlFile>, &MultiMedia)> {
+        match self.lookup(ResourceKind::MultiMedia, multi_media_id)? {
+            (f, Resource::MultiMedias(r), i) => Some((f, r.multi_medias.get(i)?)),
+            _ => None,
+        }
     }
+
     pub fn get_image_by_id(
         &self,
         image_id: StRefId,
     ) -> Option<(&OfdItem<ResourceXmlFile>, &MultiMedia)> {
-        let image = self
-            .flat()
-            .into_iter()
-            .filter_map(|(f, r)| match r {
-                Resource::MultiMedias(multi_medias) => Some((f, multi_medias)),
+        self.get_multi_media_by_id(image_id)
+            .filter(|(_, m)| m.r#type == MultiMediaType::Image)
+    }
+
+    pub fn get_audio_by_id(
+        &self,
+        audio_id: StRefId,
+    ) -> Option<(&OfdItem<ResourceXmlFile>, &MultiMedia)> {
+        self.get_multi_media_by_id(audio_id)
+            .filter(|(_, m)| m.r#type == MultiMediaType::Audio)
+    }
+
+    pub fn get_video_by_id(
+        &self,
+        video_id: StRefId,
+    ) -> Option<(&OfdItem<ResourceXmlFile>, &MultiMedia)> {
+        self.get_multi_media_by_id(video_id)
+            .filter(|(_, m)| m.r#type == MultiMediaType::Video)
+    }
+
+    pub fn get_composite_graphic_unit_by_id(
+        &self,
+        unit_id: StRefId,
+    ) -> Option<(&OfdItem<ResourceXmlFile>, &CompositeGraphicUnit)> {
+        match self.lookup(ResourceKind::CompositeGraphicUnit, unit_id)? {
+            (f, Resource::CompositeGraphicUnits(r), i) => {
+                Some((f, r.composite_graphic_units.get(i)?))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn resource_kind(resource: &Resource) -> ResourceKind {
+    match resource {
+        Resource::ColorSpaces(_) => ResourceKind::ColorSpace,
+        Resource::DrawParams(_) => ResourceKind::DrawParam,
+        Resource::Fonts(_) => ResourceKind::Font,
+        Resource::MultiMedias(_) => ResourceKind::MultiMedia,
+        Resource::CompositeGraphicUnits(_) => ResourceKind::CompositeGraphicUnit,
+    }
+}
+
+impl OfdItem<VersionXmlFile> {
+    /// files of this version, `ID` and resolved path
+    pub fn files(&self) -> Result<Vec<(&str, RelativePathBuf)>> {
+        self.file_list
+            .files
+            .iter()
+            .map(|f| Ok((f.id.as_str(), self.resolve(&f.path)?)))
+            .collect()
+    }
+
+    /// resolved `DocRoot` of this version
+    pub fn doc_root(&self) -> Result<RelativePathBuf> {
+        let path = self.resolve(&self.content.doc_root)?;
+        // some producers point `DocRoot` to a folder
+        if self.content.doc_root.to_string_lossy().ends_with('/') {
+            Ok(path.join("Document.xml"))
+        } else {
+            Ok(path)
+        }
+    }
+}
+
+impl OfdItem<ExtensionXmlFile> {
+    /// all the `ExtendData` of the extensions, resolved to package paths
+    pub fn extend_data(&self) -> Result<Vec<(&Extension, RelativePathBuf)>> {
+        self.extensions
+            .iter()
+            .flatten()
+            .flat_map(|e| e.props.iter().map(move |p| (e, p)))
+            .filter_map(|(e, p)| match p {
+                Prop::ExtendData(loc) => Some(self.resolve(loc).map(|path| (e, path))),
                 _ => None,
             })
-            .flat_map(|(fi, f)| f.multi_medias.iter().map(move |f| (fi, f)))
-            .filter(|(_, f)| f.r#type == MultiMediaType::Image)
-            .find(|(_, f)| f.id == image_id);
-        image
+            .collect()
     }
 }
 
 impl<T> OfdItem<T> {
-    pub fn resolve(&self, other: &PathBuf) -> RelativePathBuf {
-        let this = self.path.clone();
-        inner_resolve(&this, other)
+    /// path of this item in the package
+    pub fn path(&self) -> &RelativePathBuf {
+        &self.path
+    }
+
+    /// resolve a location in this item to a path in the package
+    pub fn resolve(&self, other: &Path) -> Result<RelativePathBuf> {
+        inner_resolve(&self.path, other)
     }
 }
-fn inner_resolve(this: &RelativePathBuf, other: &PathBuf) -> RelativePathBuf {
-    if other.starts_with("/") {
-        return other.relative_to("/").unwrap();
-    }
-    let that = RelativePathBuf::from_path(other).unwrap();
-    let res = if that.to_string().starts_with('/') {
-        that.normalize()
-    } else {
-        let folder = this.parent();
-        let base = match folder {
-            Some(p) => p.into(),
-            None => RelativePathBuf::new(),
-        };
-        base.join(that).normalize()
+/// find an object by id, page blocks are searched recursively
+fn find_object(objects: Option<&[VtGraphicUnit]>, object_id: StRefId) -> Option<&VtGraphicUnit> {
+    objects.into_iter().flatten().find_map(|o| match o {
+        VtGraphicUnit::TextObject(t) if t.id == object_id => Some(o),
+        VtGraphicUnit::PathObject(p) if p.id == object_id => Some(o),
+        VtGraphicUnit::ImageObject(i) if i.id == object_id => Some(o),
+        VtGraphicUnit::CompositeObject(c) if c.id == object_id => Some(o),
+        VtGraphicUnit::PageBlock(b) if b.id == object_id => Some(o),
+        VtGraphicUnit::PageBlock(b) => find_object(b.objects.as_deref(), object_id),
+        _ => None,
+    })
+}
+
+/// deserialize the root element of an item,
+/// errors carry the item path
+pub(crate) fn parse_element<T: DeserializeOwned>(path: &str, root: &Element) -> Result<T> {
+    xdom::de::from_ele(root).map_err(|source| Error::ItemDeError {
+        path: path.into(),
+        source,
+    })
+}
+
+/// parse an xml item into an element
+pub(crate) fn read_element<R: BufRead>(reader: R) -> Result<Element> {
+    let mut reader = BufReader::new(reader);
+    let buf = reader.fill_buf()?;
+
+    // UTF-8 BOM
+    // handle u+FEFF in utf-8 file
+    // just skip this three bytes
+    if buf.starts_with(&[0xef_u8, 0xbb, 0xbf]) {
+        reader.consume(3);
+    }
+    let root = Element::from_reader_with_prefixes(reader, RawOfd::<()>::OFD_ENTRY.to_string())?;
+    Ok(root)
+}
+
+/// resolve `other` against the folder of `this`, a leading `/` means the package root
+///
+/// paths going above the package root are rejected.
+pub(crate) fn inner_resolve(this: &RelativePathBuf, other: &Path) -> Result<RelativePathBuf> {
+    let other = other
+        .to_str()
+        .ok_or_else(|| Error::InvalidPath(other.to_string_lossy().into()))?;
+    let joined = match other.strip_prefix('/') {
+        Some(abs) => RelativePathBuf::from(abs),
+        None => this.parent().unwrap_or(RelativePath::new("")).join(other),
     };
-    res
+    let mut depth = 0_usize;
+    for c in joined.components() {
+        match c {
+            Component::ParentDir if depth == 0 => {
+                return Err(Error::PathEscapesRoot(other.into()));
+            }
+            Component::ParentDir => depth -= 1,
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+        }
+    }
+    Ok(joined.normalize())
 }
-pub fn from_path(path: impl AsRef<Path>) -> Result<Ofd<BufReader<File>>> {
+pub fn from_path(path: impl AsRef<Path>) -> Result<Ofd<ZipArchive<BufReader<File>>>> {
     let f = File::open(path)?;
-    let reader = BufReader::new(f);
+    from_reader(BufReader::new(f))
+}
+
+pub fn from_bytes<B: AsRef<[u8]>>(bytes: B) -> Result<Ofd<ZipArchive<Cursor<B>>>> {
+    from_reader(Cursor::new(bytes))
+}
+
+/// open an ofd package from any seekable reader, such as a download buffer
+pub fn from_reader<R: Read + Seek>(reader: R) -> Result<Ofd<ZipArchive<R>>> {
     let zip = ZipArchive::new(reader)?;
+    from_storage(zip)
+}
 
-    let _ = zip
-        .index_for_name("OFD.xml")
-        .ok_or(Error::OfdEntryNotFound)?;
-    Ok(Ofd::from_raw(RawOfd {
-        zip_archive: zip,
-        cache: HashMap::new(),
-    }))
+/// open an ofd file by memory mapping it, items are read from the mapping directly
+///
+/// # Safety
+///
+/// the file must not be modified or truncated while the returned [Ofd] is alive,
+/// see [memmap2::Mmap::map].
+#[cfg(feature = "mmap")]
+pub unsafe fn from_path_mmap(
+    path: impl AsRef<Path>,
+) -> Result<Ofd<ZipArchive<Cursor<memmap2::Mmap>>>> {
+    let file = File::open(path)?;
+    let mmap = memmap2::Mmap::map(&file)?;
+    from_bytes(mmap)
 }
 
-pub fn from_bytes<'a, 'b, B: AsRef<[u8]> + 'b>(bytes: B) -> Result<Ofd<Cursor<B>>> {
-    let zip = ZipArchive::new(Cursor::new(bytes))?;
+/// open an extracted ofd package, `path` is the directory holding `OFD.xml`
+pub fn from_dir(path: impl AsRef<Path>) -> Result<Ofd<DirStorage>> {
+    from_storage(DirStorage::new(path))
+}
 
-    let _ = zip
-        .index_for_name("OFD.xml")
-        .ok_or(Error::OfdEntryNotFound)?;
-    Ok(Ofd::from_raw(RawOfd {
-        zip_archive: zip,
-        cache: HashMap::new(),
-    }))
+pub fn from_storage<S: Storage>(storage: S) -> Result<Ofd<S>> {
+    check_entry(&storage)?;
+    Ok(Ofd::from_raw(RawOfd::new(storage)))
+}
+
+/// a package must hold `OFD.xml`
+pub(crate) fn check_entry<S: Storage>(storage: &S) -> Result<()> {
+    match storage.contains(RawOfd::<S>::OFD_ENTRY) {
+        true => Ok(()),
+        false => Err(Error::OfdEntryNotFound),
+    }
 }
 
 #[cfg(test)]
@@ -702,7 +1442,10 @@ mod tests {
 
     use relative_path::RelativePathBuf;
 
-    use super::OfdItem;
+    use super::{parse_element, read_element, OfdItem, Resources};
+    use crate::error::Error;
+    use ofd_base::file::res::ResourceXmlFile;
+    use std::rc::Rc;
 
     #[test]
     fn test_relative_path() {
@@ -717,9 +1460,70 @@ mod tests {
     fn test_resolve() {
         let l = OfdItem {
             path: RelativePathBuf::from("a/b"),
-            content: String::new(),
+            content: Rc::new(String::new()),
         };
-        let r = l.resolve(&PathBuf::from("value".to_string()));
-        assert_eq!(r.to_string(), "a/value")
+        let r = l.resolve(&PathBuf::from("value".to_string())).unwrap();
+        assert_eq!(r.to_string(), "a/value");
+        let r = l.resolve(&PathBuf::from("/c/./d/../value")).unwrap();
+        assert_eq!(r.to_string(), "c/value");
+        let r = l.resolve(&PathBuf::from("../value")).unwrap();
+        assert_eq!(r.to_string(), "value");
+        assert!(matches!(
+            l.resolve(&PathBuf::from("../../value")),
+            Err(Error::PathEscapesRoot(_))
+        ));
+        assert!(matches!(
+            l.resolve(&PathBuf::from("/../value")),
+            Err(Error::PathEscapesRoot(_))
+        ));
+    }
+
+    fn res_file(path: &str, xml: &str) -> Vec<OfdItem<ResourceXmlFile>> {
+        let root = read_element(xml.as_bytes()).unwrap();
+        vec![OfdItem {
+            path: path.into(),
+            content: Rc::new(parse_element(path, &root).unwrap()),
+        }]
+    }
+
+    #[test]
+    fn test_resources() {
+        let public = res_file(
+            "Doc_0/PublicRes.xml",
+            r#"<ofd:Res xmlns:ofd="http://www.ofdspec.org/2016" BaseLoc="Res">
+                <ofd:Fonts><ofd:Font ID="1" FontName="public"/><ofd:Font ID="2" FontName="shared"/></ofd:Fonts>
+                <ofd:MultiMedias>
+                    <ofd:MultiMedia ID="3" Type="Image"><ofd:MediaFile>a.png</ofd:MediaFile></ofd:MultiMedia>
+                    <ofd:MultiMedia ID="4" Type="Audio"><ofd:MediaFile>a.mp3</ofd:MediaFile></ofd:MultiMedia>
+                    <ofd:MultiMedia ID="5" Type="Video"><ofd:MediaFile>a.mp4</ofd:MediaFile></ofd:MultiMedia>
+                </ofd:MultiMedias>
+            </ofd:Res>"#,
+        );
+        let page = res_file(
+            "Doc_0/Pages/Page_0/PageRes.xml",
+            r#"<ofd:Res xmlns:ofd="http://www.ofdspec.org/2016" BaseLoc="Res">
+                <ofd:Fonts><ofd:Font ID="2" FontName="page"/></ofd:Fonts>
+            </ofd:Res>"#,
+        );
+        let res = Resources::new(None, Some(public), None, Some(page));
+        assert_eq!(res.iter().count(), 2);
+        assert_eq!(res.get_font_by_id(1).unwrap().1.font_name, "public");
+        let (file, font) = res.get_font_by_id(2).unwrap();
+        assert_eq!(font.font_name, "page");
+        assert_eq!(file.path(), "Doc_0/Pages/Page_0/PageRes.xml");
+        assert!(res.get_font_by_id(3).is_none());
+
+        assert!(res.get_image_by_id(3).is_some());
+        assert!(res.get_image_by_id(4).is_none());
+        assert_eq!(
+            res.get_audio_by_id(4).unwrap().1.media_file.to_str(),
+            Some("a.mp3")
+        );
+        assert_eq!(
+            res.get_video_by_id(5).unwrap().1.media_file.to_str(),
+            Some("a.mp4")
+        );
+        assert!(res.get_multi_media_by_id(5).is_some());
+        assert!(res.get_composite_graphic_unit_by_id(1).is_none());
     }
 }
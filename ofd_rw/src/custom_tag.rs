@@ -0,0 +1,222 @@
+use crate::error::{Error, Result};
+use ofd_base::StRefId;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use relative_path::RelativePathBuf;
+use std::io::BufRead;
+
+/// a reference from a custom tag to an object on a page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectRef {
+    /// `ID` of the page, not the index
+    pub page_ref: StRefId,
+    pub object_id: StRefId,
+}
+
+/// an element in a custom tag file
+///
+/// tag files follow a schema defined by the producer,
+/// so they are kept as a tree of names and object references.
+#[derive(Debug, Clone, Default)]
+pub struct TagElement {
+    /// name without prefix
+    pub name: String,
+
+    /// trimmed text content, `ObjectRef` excluded
+    pub text: Option<String>,
+
+    /// page objects this element points to
+    pub object_refs: Vec<ObjectRef>,
+
+    pub children: Vec<TagElement>,
+}
+
+impl TagElement {
+    /// first child named `name`
+    pub fn child(&self, name: &str) -> Option<&TagElement> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    /// find a descendant by a `/` separated path relative to this element,
+    /// e.g. `Buyer/BuyerName`
+    pub fn find(&self, path: &str) -> Option<&TagElement> {
+        path.split('/')
+            .filter(|s| !s.is_empty())
+            .try_fold(self, |e, name| e.child(name))
+    }
+
+    /// all the descendants of this element, depth first,
+    /// with their paths relative to this element
+    pub fn descendants(&self) -> Vec<(String, &TagElement)> {
+        let mut res = vec![];
+        for child in self.children.iter() {
+            res.push((child.name.clone(), child));
+            for (path, e) in child.descendants() {
+                res.push((format!("{}/{}", child.name, path), e));
+            }
+        }
+        res
+    }
+}
+
+/// a custom tag file, loaded from `FileLoc` of a `CustomTag`
+#[derive(Debug, Clone)]
+pub struct TagFile {
+    pub type_id: String,
+    pub namespace: Option<String>,
+
+    /// resolved `SchemaLoc`
+    pub schema_loc: Option<RelativePathBuf>,
+
+    pub root: TagElement,
+}
+
+impl TagFile {
+    /// elements pointing to an object, with their paths relative to the root
+    pub fn elements_for_object(
+        &self,
+        page_ref: StRefId,
+        object_id: StRefId,
+    ) -> Vec<(String, &TagElement)> {
+        let target = ObjectRef {
+            page_ref,
+            object_id,
+        };
+        self.root
+            .descendants()
+            .into_iter()
+            .filter(|(_, e)| e.object_refs.contains(&target))
+            .collect()
+    }
+}
+
+/// parse a custom tag file
+///
+/// namespaces are not checked, some producers write files like
+/// `<:eInvoice xmlns:="">` which are rejected by a conforming parser.
+pub(crate) fn read_tag_file<R: BufRead>(reader: R) -> Result<TagElement> {
+    let mut reader = Reader::from_reader(reader);
+    let mut buf = Vec::new();
+    // open elements, the last one is the innermost
+    let mut stack: Vec<TagElement> = vec![];
+    // the `ObjectRef` being read
+    let mut object_ref: Option<(StRefId, String)> = None;
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) if is_object_ref(&e) => {
+                object_ref = Some((page_ref(&e)?, String::new()));
+            }
+            Event::Start(e) => stack.push(new_element(&e)),
+            Event::Empty(e) if !is_object_ref(&e) => {
+                let element = new_element(&e);
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Ok(element),
+                }
+            }
+            Event::Text(t) => {
+                let text = t.unescape()?;
+                if let Some((_, id)) = object_ref.as_mut() {
+                    id.push_str(&text);
+                } else if let Some(e) = stack.last_mut() {
+                    let text = text.trim();
+                    if !text.is_empty() {
+                        e.text.get_or_insert_with(String::new).push_str(text);
+                    }
+                }
+            }
+            Event::End(_) => {
+                if let Some((page_ref, id)) = object_ref.take() {
+                    let object_id = id
+                        .trim()
+                        .parse()
+                        .map_err(|_| Error::InvalidCustomTag(format!("ObjectRef \"{}\"", id)))?;
+                    if let Some(e) = stack.last_mut() {
+                        e.object_refs.push(ObjectRef {
+                            page_ref,
+                            object_id,
+                        });
+                    }
+                    continue;
+                }
+                let element = stack
+                    .pop()
+                    .ok_or_else(|| Error::InvalidCustomTag("unexpected end tag".into()))?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Ok(element),
+                }
+            }
+            Event::Eof => return Err(Error::InvalidCustomTag("unexpected eof".into())),
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+fn is_object_ref(e: &BytesStart) -> bool {
+    e.local_name().as_ref() == b"ObjectRef"
+}
+
+fn new_element(e: &BytesStart) -> TagElement {
+    TagElement {
+        name: String::from_utf8_lossy(e.local_name().as_ref()).into_owned(),
+        ..Default::default()
+    }
+}
+
+fn page_ref(e: &BytesStart) -> Result<StRefId> {
+    for attr in e.attributes() {
+        let attr = attr.map_err(quick_xml::Error::from)?;
+        if attr.key.local_name().as_ref() == b"PageRef" {
+            let value = attr.unescape_value()?;
+            return value
+                .trim()
+                .parse()
+                .map_err(|_| Error::InvalidCustomTag(format!("PageRef \"{}\"", value)));
+        }
+    }
+    Err(Error::InvalidCustomTag("ObjectRef without PageRef".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::Result;
+    use std::fs::File;
+    use std::io::BufReader;
+
+    #[test]
+    fn test_read_tag_file() -> Result<()> {
+        let file = File::open("../samples/000/Doc_0/Tags/CustomTag.xml")?;
+        let root = read_tag_file(BufReader::new(file))?;
+        assert_eq!(root.name, "eInvoice");
+
+        let buyer_name = root.find("Buyer/BuyerName").unwrap();
+        assert_eq!(
+            buyer_name.object_refs,
+            vec![ObjectRef {
+                page_ref: 1,
+                object_id: 65
+            }]
+        );
+        let amount = root.find("TaxInclusiveTotalAmount").unwrap();
+        assert_eq!(amount.object_refs.len(), 2);
+        assert!(root.find("Buyer/NotExists").is_none());
+        assert!(root
+            .descendants()
+            .iter()
+            .any(|(p, _)| p == "GoodsInfos/GoodsInfo/Item"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_text() -> Result<()> {
+        let xml = r#"<a:Root xmlns:a="x"><Name> hello </Name><Empty/></a:Root>"#;
+        let root = read_tag_file(xml.as_bytes())?;
+        assert_eq!(root.name, "Root");
+        assert_eq!(root.find("Name").unwrap().text.as_deref(), Some("hello"));
+        assert!(root.find("Empty").is_some());
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+use crate::container::{inner_resolve, Ofd, RawOfd};
+use crate::error::{Error, Result};
+use crate::storage::Storage;
+use crate::writer::{item_name, to_xml_bytes, OfdWriter, OfdXml};
+use ofd_base::file::document::DocumentXmlFile;
+use ofd_base::file::ofd::OfdXmlFile;
+use ofd_base::file::page::PageXmlFile;
+use relative_path::RelativePathBuf;
+use serde::de::DeserializeOwned;
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Cursor, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// an edited xml item, it will be serialized again on save
+trait EditItem: Any {
+    fn to_bytes(&self) -> Result<Vec<u8>>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: OfdXml + 'static> EditItem for T {
+    fn to_bytes(&self) -> Result<Vec<u8>> {
+        to_xml_bytes(self)
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+enum Edit {
+    Xml(Box<dyn EditItem>),
+    Bytes(Vec<u8>),
+    Removed,
+}
+
+/// an editing session on an ofd package
+///
+/// items are loaded from the source [Ofd] on first mutable access
+/// and kept in the session until [OfdEditor::save_to].
+/// all the items never touched are copied byte-for-byte.
+pub struct OfdEditor<S> {
+    ofd: Ofd<S>,
+    edits: BTreeMap<String, Edit>,
+}
+
+impl<S: Storage> OfdEditor<S> {
+    pub fn new(ofd: Ofd<S>) -> Self {
+        Self {
+            ofd,
+            edits: BTreeMap::new(),
+        }
+    }
+
+    /// the source package
+    pub fn source(&self) -> &Ofd<S> {
+        &self.ofd
+    }
+
+    /// is there any change in this session
+    pub fn is_modified(&self) -> bool {
+        !self.edits.is_empty()
+    }
+
+    /// mutable access to `OFD.xml`
+    pub fn entry_mut(&mut self) -> Result<&mut OfdXmlFile> {
+        self.xml_mut(RawOfd::<S>::OFD_ENTRY)
+    }
+
+    /// mutable access to the `Document.xml` of a document
+    pub fn document_mut(&mut self, doc_index: usize) -> Result<&mut DocumentXmlFile> {
+        let path = self.document_path(doc_index)?;
+        self.xml_mut(path.as_str())
+    }
+
+    /// mutable access to the `Content.xml` of a page
+    pub fn page_mut(&mut self, doc_index: usize, page_index: usize) -> Result<&mut PageXmlFile> {
+        let doc_path = self.document_path(doc_index)?;
+        let doc = self.current::<DocumentXmlFile>(doc_path.as_str())?;
+        let page = doc.pages.page.get(page_index).ok_or(Error::NoSuchPage)?;
+        let path = inner_resolve(&doc_path, &page.base_loc)?;
+        self.xml_mut(path.as_str())
+    }
+
+    /// mutable access to any xml item in the package,
+    /// such as a resource file.
+    pub fn xml_mut<T>(&mut self, path: impl AsRef<str>) -> Result<&mut T>
+    where
+        T: OfdXml + DeserializeOwned + Clone + 'static,
+    {
+        let name = item_name(path.as_ref());
+        let loaded = match self.edits.get(&name) {
+            Some(Edit::Xml(_)) => None,
+            Some(Edit::Bytes(bytes)) => Some(RawOfd::<S>::read_item(&name, Cursor::new(bytes))?),
+            Some(Edit::Removed) => return Err(not_found(&name)),
+            None => Some(Rc::unwrap_or_clone(
+                self.ofd.xml_by_path::<T>(name.as_str())?,
+            )),
+        };
+        if let Some(xml) = loaded {
+            self.edits.insert(name.clone(), Edit::Xml(Box::new(xml)));
+        }
+        match self.edits.get_mut(&name) {
+            Some(Edit::Xml(item)) => item
+                .as_any_mut()
+                .downcast_mut::<T>()
+                .ok_or(Error::ItemTypeMismatch(name)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// add or replace an xml item
+    pub fn put_xml<T: OfdXml + 'static>(&mut self, path: impl AsRef<str>, value: T) {
+        let name = item_name(path.as_ref());
+        self.edits.insert(name, Edit::Xml(Box::new(value)));
+    }
+
+    /// add or replace a raw item
+    pub fn put_bytes(&mut self, path: impl AsRef<str>, bytes: impl Into<Vec<u8>>) {
+        let name = item_name(path.as_ref());
+        self.edits.insert(name, Edit::Bytes(bytes.into()));
+    }
+
+    /// remove an item from the package
+    pub fn remove(&mut self, path: impl AsRef<str>) {
+        let name = item_name(path.as_ref());
+        self.edits.insert(name, Edit::Removed);
+    }
+
+    /// does the edited package contain an item
+    pub fn contains(&self, path: impl AsRef<str>) -> bool {
+        let name = item_name(path.as_ref());
+        match self.edits.get(&name) {
+            Some(Edit::Removed) => false,
+            Some(_) => true,
+            None => self.ofd.contains(&name),
+        }
+    }
+
+    /// write the edited package into `writer`
+    pub fn save_to<W: Write + Seek>(&self, writer: W) -> Result<W> {
+        let mut writer = OfdWriter::new(writer);
+        // keep the order of the source package
+        for name in self.ofd.item_names() {
+            match self.edits.get(&name) {
+                Some(edit) => Self::write_edit(&mut writer, &name, edit)?,
+                None => self.ofd.raw_copy_to(&name, &mut writer)?,
+            }
+        }
+        // then the new items
+        let source_names = self.ofd.item_names();
+        for (name, edit) in self.edits.iter() {
+            if !source_names.contains(name) {
+                Self::write_edit(&mut writer, name, edit)?;
+            }
+        }
+        writer.finish()
+    }
+
+    /// write the edited package to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = File::create(path)?;
+        let mut w = self.save_to(BufWriter::new(file))?;
+        w.flush()?;
+        Ok(())
+    }
+
+    fn write_edit<W: Write + Seek>(
+        writer: &mut OfdWriter<W>,
+        name: &str,
+        edit: &Edit,
+    ) -> Result<()> {
+        match edit {
+            Edit::Xml(item) => writer.write_bytes(name, &item.to_bytes()?),
+            Edit::Bytes(bytes) => writer.write_bytes(name, bytes),
+            Edit::Removed => Ok(()),
+        }
+    }
+
+    /// current state of an xml item, edited or not
+    pub(crate) fn current<T>(&self, name: &str) -> Result<T>
+    where
+        T: DeserializeOwned + Clone + 'static,
+    {
+        match self.edits.get(name) {
+            Some(Edit::Xml(item)) => item
+                .as_any()
+                .downcast_ref::<T>()
+                .cloned()
+                .ok_or(Error::ItemTypeMismatch(name.into())),
+            Some(Edit::Bytes(bytes)) => RawOfd::<S>::read_item(name, Cursor::new(bytes)),
+            Some(Edit::Removed) => Err(not_found(name)),
+            None => Ok(Rc::unwrap_or_clone(self.ofd.xml_by_path::<T>(name)?)),
+        }
+    }
+
+    pub(crate) fn document_path(&self, doc_index: usize) -> Result<RelativePathBuf> {
+        let entry = self.current::<OfdXmlFile>(RawOfd::<S>::OFD_ENTRY)?;
+        let doc_body = entry.doc_body.get(doc_index).ok_or(Error::NoSuchDocument)?;
+        let doc_root: &PathBuf = doc_body.doc_root.as_ref().ok_or(Error::NoSuchDocument)?;
+        inner_resolve(&RelativePathBuf::from(RawOfd::<S>::OFD_ENTRY), doc_root)
+    }
+}
+
+fn not_found(name: &str) -> Error {
+    Error::ItemNotFound(name.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_bytes, from_path};
+    use eyre::Result;
+    use ofd_base::file::res::ResourceXmlFile;
+
+    #[test]
+    fn test_edit_and_save() -> Result<()> {
+        let ofd = from_path("../samples/000.ofd")?;
+        let mut editor = OfdEditor::new(ofd.clone());
+        assert!(!editor.is_modified());
+
+        editor.entry_mut()?.doc_body[0].doc_info.title = Some("edited".into());
+        editor.document_mut(0)?.common_data.max_unit_id = 100;
+        editor.page_mut(0, 0)?.area = None;
+        let res = editor.xml_mut::<ResourceXmlFile>("/Doc_0/PublicRes.xml")?;
+        res.base_loc = "Fonts".into();
+        editor.put_bytes("Doc_0/Attachs/new.txt", b"hello".to_vec());
+        editor.remove("Doc_0/Res/image_78.jb2");
+        assert!(editor.is_modified());
+
+        let out = editor.save_to(Cursor::new(Vec::new()))?.into_inner();
+        let saved = from_bytes(out)?;
+
+        let title = &saved.entry()?.doc_body[0].doc_info.title;
+        assert_eq!(title.as_deref(), Some("edited"));
+        assert_eq!(saved.document_by_index(0)?.common_data.max_unit_id, 100);
+        assert!(saved.page_by_index(0, 0)?.area.is_none());
+        assert_eq!(saved.bytes("Doc_0/Attachs/new.txt")?, b"hello");
+        assert!(saved.bytes("Doc_0/Res/image_78.jb2").is_err());
+        let path = "Doc_0/Signs/Sign_0/SignedValue.dat";
+        assert_eq!(saved.bytes(path)?, ofd.bytes(path)?);
+        let path = "Doc_0/Tpls/Tpl_0/Content.xml";
+        assert_eq!(saved.bytes(path)?, ofd.bytes(path)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_xml_mut_type_mismatch() -> Result<()> {
+        let ofd = from_path("../samples/000.ofd")?;
+        let mut editor = OfdEditor::new(ofd);
+        editor.document_mut(0)?;
+        let res = editor.xml_mut::<PageXmlFile>("Doc_0/Document.xml");
+        assert!(matches!(res, Err(Error::ItemTypeMismatch(_))));
+        Ok(())
+    }
+}
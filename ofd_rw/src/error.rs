@@ -6,6 +6,9 @@ pub enum Error {
     #[error(r#"{0}. path: "{1}""#)]
     OpenZipError(ZipError, String),
 
+    #[error(r#"item not found. path: "{0}""#)]
+    ItemNotFound(String),
+
     #[error("ofd entry not found")]
     OfdEntryNotFound,
 
@@ -15,6 +18,54 @@ pub enum Error {
     #[error("no such template")]
     NoSuchTemplate,
 
+    #[error("no such version")]
+    NoSuchVersion,
+
+    #[error("no such page")]
+    NoSuchPage,
+
+    #[error(r#"item type mismatch. path: "{0}""#)]
+    ItemTypeMismatch(String),
+
+    #[error(r#"invalid path: "{0}""#)]
+    InvalidPath(String),
+
+    #[error(r#"path escapes the package root: "{0}""#)]
+    PathEscapesRoot(String),
+
+    #[error(r#"item larger than {max} bytes. path: "{path}""#)]
+    ItemTooLarge { path: String, max: u64 },
+
+    #[error(r#"compression ratio of item over {max}. path: "{path}""#)]
+    CompressionRatioExceeded { path: String, max: u64 },
+
+    #[error("too many items in package: {count}, max {max}")]
+    TooManyItems { count: usize, max: usize },
+
+    #[error("package larger than {max} bytes: {size}")]
+    PackageTooLarge { size: u64, max: u64 },
+
+    #[error(r#"total size of items read over {max} bytes. path: "{path}""#)]
+    TotalSizeExceeded { path: String, max: u64 },
+
+    #[error(r#"xml elements nested deeper than {max}. path: "{path}""#)]
+    XmlTooDeep { path: String, max: usize },
+
+    #[error(r#"invalid page range: "{0}""#)]
+    InvalidPageRange(String),
+
+    #[error("page order is not a permutation of the pages")]
+    InvalidPageOrder,
+
+    #[error("no page selected")]
+    NoPageSelected,
+
+    #[error("nothing to merge")]
+    NothingToMerge,
+
+    #[error("invalid custom tag: {0}")]
+    InvalidCustomTag(String),
+
     #[error("error parse xml: {0}")]
     MiniDomError(#[from] minidom::Error),
 
@@ -24,8 +75,48 @@ pub enum Error {
     #[error("{0}")]
     XmlDeError(#[from] xdom::de::XmlDeError),
 
+    /// an xml item failing to deserialize,
+    /// see [xdom::de::XmlDeError::element_path] for where in the item
+    #[error(r#"{source}. path: "{path}""#)]
+    ItemDeError {
+        path: String,
+        source: xdom::de::XmlDeError,
+    },
+
+    #[error("{0}")]
+    XmlSerError(#[from] xdom::ser::XmlSerErr),
+
+    #[error("{0}")]
+    QuickXmlError(#[from] quick_xml::Error),
+
     #[error("{0}")]
     ZipError(#[from] ZipError),
 }
 
+impl Error {
+    /// path of the item failing to deserialize
+    pub fn item_path(&self) -> Option<&str> {
+        match self {
+            Error::ItemDeError { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// path of the element failing to deserialize, like `/Page/Content/Layer[@ID=2]`
+    pub fn element_path(&self) -> Option<&str> {
+        match self {
+            Error::ItemDeError { source, .. } | Error::XmlDeError(source) => source.element_path(),
+            _ => None,
+        }
+    }
+
+    /// is it caused by a missing item
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            Error::ItemNotFound(_) | Error::OpenZipError(ZipError::FileNotFound, _)
+        )
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,308 @@
+use crate::container::{inner_resolve, Ofd};
+use crate::error::{Error, Result};
+use crate::renumber::VisitIds;
+use crate::storage::Storage;
+use crate::writer::{to_xml_bytes, OfdWriter};
+use ofd_base::common::{ActionType, Actions, VtTo};
+use ofd_base::file::annotation::AnnotationsXmlFile;
+use ofd_base::file::document::{CtOutlineElem, DocumentXmlFile};
+use ofd_base::file::res::{Resource, ResourceXmlFile};
+use ofd_base::{StId, StLoc};
+use relative_path::RelativePathBuf;
+use std::collections::{HashMap, HashSet};
+use std::io::{Seek, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// zero based page indices from ranges counting from 1, like `1-3,7` or `5-`
+///
+/// pages are kept in the given order, a page given twice is taken once.
+pub fn parse_page_ranges(ranges: &str, page_count: usize) -> Result<Vec<usize>> {
+    let invalid = || Error::InvalidPageRange(ranges.into());
+    let mut pages = vec![];
+    for part in ranges.split(',') {
+        let part = part.trim();
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (start.trim(), end.trim()),
+            None => (part, part),
+        };
+        let start = start.parse::<usize>().map_err(|_| invalid())?;
+        let end = match end {
+            "" => page_count,
+            end => end.parse::<usize>().map_err(|_| invalid())?,
+        };
+        if start == 0 || start > end || end > page_count {
+            return Err(invalid());
+        }
+        for page in start - 1..end {
+            if !pages.contains(&page) {
+                pages.push(page);
+            }
+        }
+    }
+    Ok(pages)
+}
+
+impl<S: Storage> Ofd<S> {
+    /// copy some pages of a document into a new package, in the given order
+    ///
+    /// only the templates, resources, annotations and resource files
+    /// used by these pages are copied, `ID`s stay as they are.
+    /// outlines and bookmarks pointing to dropped pages are removed.
+    /// signatures, attachments, custom tags, extensions and versions are left out.
+    pub fn extract_pages<W: Write + Seek>(
+        &self,
+        doc_index: usize,
+        pages: &[usize],
+        writer: W,
+    ) -> Result<W> {
+        if pages.is_empty() {
+            return Err(Error::NoPageSelected);
+        }
+        let doc = self.document_by_index(doc_index)?;
+        let page_refs = &doc.pages.page;
+        if pages.iter().any(|p| *p >= page_refs.len()) {
+            return Err(Error::NoSuchPage);
+        }
+
+        // items copied as they are
+        let mut keep = HashSet::new();
+        let mut ids: Vec<StId> = vec![];
+        let mut res_files: Vec<(RelativePathBuf, ResourceXmlFile)> = vec![];
+        let mut add_res = |path: &RelativePathBuf, res: &ResourceXmlFile| {
+            if !res_files.iter().any(|(p, _)| p == path) {
+                res_files.push((path.clone(), res.clone()));
+            }
+        };
+        let mut template_ids = HashSet::new();
+        for &page_index in pages {
+            let page = self.page_by_index(doc_index, page_index)?;
+            keep.insert(page.path().to_string());
+            Rc::unwrap_or_clone(page.content.clone()).visit_ids(&mut ids);
+            for tpl in page.template.iter().flatten() {
+                template_ids.insert(tpl.template_id);
+            }
+            for res in self.resources_for_page(doc_index, page_index)?.iter() {
+                add_res(res.path(), res);
+            }
+            for tpl in self.templates_for_page(doc_index, page_index)? {
+                keep.insert(tpl.path().to_string());
+                Rc::unwrap_or_clone(tpl.content.clone()).visit_ids(&mut ids);
+                for loc in tpl.page_res.iter().flatten() {
+                    let path = tpl.resolve(loc)?;
+                    let res = self.xml_by_path::<ResourceXmlFile>(path.as_str())?;
+                    add_res(&path, &res);
+                }
+            }
+            for annot in self.annotations_for_page(doc_index, page_index)? {
+                keep.insert(annot.path().to_string());
+                Rc::unwrap_or_clone(annot.content.clone()).visit_ids(&mut ids);
+            }
+        }
+
+        // the document with what points to dropped pages removed
+        let mut document = Rc::unwrap_or_clone(doc.content.clone());
+        document.pages.page = pages.iter().map(|i| page_refs[*i].clone()).collect();
+        if let Some(tpls) = &mut document.common_data.template_page {
+            tpls.retain(|t| template_ids.contains(&t.id));
+        }
+        let filter = ActionFilter::prune(&mut document);
+        document.custom_tags = None;
+        document.attachments = None;
+        document.extensions = None;
+        document.clone().visit_ids(&mut ids);
+
+        // items written anew
+        let mut items = HashMap::new();
+        if let Some(loc) = &document.annotations {
+            let path = doc.resolve(loc)?;
+            let mut annotations =
+                Rc::unwrap_or_clone(self.xml_by_path::<AnnotationsXmlFile>(path.as_str())?);
+            if let Some(pages) = &mut annotations.page {
+                pages.retain(|p| filter.page_ids.contains(&p.page_id));
+            }
+            match annotations.page.as_ref().is_some_and(|p| !p.is_empty()) {
+                true => {
+                    items.insert(path.to_string(), to_xml_bytes(&annotations)?);
+                }
+                false => document.annotations = None,
+            }
+        }
+        items.insert(doc.path().to_string(), to_xml_bytes(&document)?);
+
+        // resources used, and the resources they use in turn
+        let mut ids = ids.into_iter().collect::<HashSet<_>>();
+        loop {
+            let mut used = vec![];
+            for (_, res) in res_files.iter() {
+                let mut res = res.clone();
+                retain_resources(&mut res, &ids);
+                res.visit_ids(&mut used);
+            }
+            let count = ids.len();
+            ids.extend(used);
+            if ids.len() == count {
+                break;
+            }
+        }
+        for (path, mut res) in res_files {
+            retain_resources(&mut res, &ids);
+            for loc in resource_files(&res) {
+                keep.insert(inner_resolve(&path, &res.base_loc.join(loc))?.to_string());
+            }
+            items.insert(path.to_string(), to_xml_bytes(&res)?);
+        }
+
+        let mut entry = Rc::unwrap_or_clone(self.entry()?.content);
+        let mut doc_body = entry.doc_body.swap_remove(doc_index);
+        doc_body.doc_root = Some(PathBuf::from(doc.path().as_str()));
+        doc_body.versions = None;
+        doc_body.signatures = None;
+        entry.doc_body = vec![doc_body];
+
+        let mut writer = OfdWriter::new(writer);
+        writer.write_entry(&entry)?;
+        // keep the order of the source package
+        for name in self.item_names() {
+            match items.get(&name) {
+                Some(bytes) => writer.write_bytes(&name, bytes)?,
+                None if keep.contains(&name) => self.raw_copy_to(&name, &mut writer)?,
+                None => {}
+            }
+        }
+        writer.finish()
+    }
+}
+
+/// drops actions leading to pages or bookmarks gone
+pub(crate) struct ActionFilter {
+    page_ids: HashSet<StId>,
+    bookmarks: HashSet<String>,
+}
+
+impl ActionFilter {
+    /// remove the bookmarks, outlines and document actions
+    /// leading to pages no longer in the document
+    pub(crate) fn prune(document: &mut DocumentXmlFile) -> Self {
+        let page_ids = document
+            .pages
+            .page
+            .iter()
+            .map(|p| p.id)
+            .collect::<HashSet<_>>();
+        if let Some(bookmarks) = &mut document.bookmarks {
+            bookmarks
+                .bookmarks
+                .retain(|b| page_ids.contains(&b.dest.page_id));
+        }
+        let filter = ActionFilter {
+            page_ids,
+            bookmarks: document
+                .bookmarks
+                .iter()
+                .flat_map(|b| b.bookmarks.iter().map(|b| b.name.clone()))
+                .collect(),
+        };
+        if let Some(outlines) = &mut document.outlines {
+            filter.outlines(&mut outlines.outline_elems);
+        }
+        document.outlines = document
+            .outlines
+            .take()
+            .filter(|o| !o.outline_elems.is_empty());
+        document.bookmarks = document
+            .bookmarks
+            .take()
+            .filter(|b| !b.bookmarks.is_empty());
+        document.actions = document.actions.take().and_then(|a| filter.actions(a));
+        filter
+    }
+
+    fn actions(&self, mut actions: Actions) -> Option<Actions> {
+        actions.actions.retain(|a| match &a.action_type {
+            ActionType::Goto {
+                value: VtTo::Dest(dest),
+            } => self.page_ids.contains(&dest.page_id),
+            ActionType::Goto {
+                value: VtTo::Bookmark { name },
+            } => self.bookmarks.contains(name),
+            _ => true,
+        });
+        (!actions.actions.is_empty()).then_some(actions)
+    }
+
+    /// an outline is kept if it still leads somewhere, or has children kept
+    fn outlines(&self, elems: &mut Vec<CtOutlineElem>) {
+        elems.retain_mut(|elem| {
+            let had_actions = elem.actions.is_some();
+            elem.actions = elem.actions.take().and_then(|a| self.actions(a));
+            if let Some(children) = &mut elem.outline_elems {
+                self.outlines(children);
+                elem.count = elem.count.map(|_| children.len() as u32);
+            }
+            elem.outline_elems = elem.outline_elems.take().filter(|c| !c.is_empty());
+            elem.actions.is_some() || elem.outline_elems.is_some() || !had_actions
+        });
+    }
+}
+
+/// keep the resources with the given `ID`s
+fn retain_resources(res: &mut ResourceXmlFile, ids: &HashSet<StId>) {
+    let Some(resources) = &mut res.resources else {
+        return;
+    };
+    resources.retain_mut(|resource| match resource {
+        Resource::ColorSpaces(r) => {
+            r.color_spaces.retain(|cs| ids.contains(&cs.id));
+            !r.color_spaces.is_empty()
+        }
+        Resource::DrawParams(r) => {
+            r.draw_params.retain(|d| ids.contains(&d.id));
+            !r.draw_params.is_empty()
+        }
+        Resource::Fonts(r) => {
+            r.fonts.retain(|f| ids.contains(&f.id));
+            !r.fonts.is_empty()
+        }
+        Resource::MultiMedias(r) => {
+            r.multi_medias.retain(|m| ids.contains(&m.id));
+            !r.multi_medias.is_empty()
+        }
+        Resource::CompositeGraphicUnits(r) => {
+            r.composite_graphic_units.retain(|u| ids.contains(&u.id));
+            !r.composite_graphic_units.is_empty()
+        }
+    });
+}
+
+/// font files, media files and color profiles, relative to `BaseLoc`
+fn resource_files(res: &ResourceXmlFile) -> Vec<&StLoc> {
+    let mut files = vec![];
+    for resource in res.resources.iter().flatten() {
+        match resource {
+            Resource::ColorSpaces(r) => {
+                files.extend(r.color_spaces.iter().filter_map(|cs| cs.profile.as_ref()))
+            }
+            Resource::Fonts(r) => files.extend(r.fonts.iter().filter_map(|f| f.font_file.as_ref())),
+            Resource::MultiMedias(r) => files.extend(r.multi_medias.iter().map(|m| &m.media_file)),
+            _ => {}
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_page_ranges() {
+        assert_eq!(parse_page_ranges("1-3,7", 10).unwrap(), [0, 1, 2, 6]);
+        assert_eq!(parse_page_ranges(" 4, 2-3 ,3", 10).unwrap(), [3, 1, 2]);
+        assert_eq!(parse_page_ranges("9-", 10).unwrap(), [8, 9]);
+        for ranges in ["", "0", "3-2", "11", "1,,2", "a-b"] {
+            let res = parse_page_ranges(ranges, 10);
+            assert!(matches!(res, Err(Error::InvalidPageRange(_))), "{ranges}");
+        }
+    }
+}
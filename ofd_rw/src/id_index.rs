@@ -0,0 +1,347 @@
+use crate::error::Result;
+use ofd_base::file::annotation::{AnnotationXmlFile, AnnotationsXmlFile};
+use ofd_base::file::document::DocumentXmlFile;
+use ofd_base::file::page::{Layer, PageXmlFile, VtGraphicUnit};
+use ofd_base::file::res::{Resource, ResourceXmlFile};
+use ofd_base::{StId, StLoc};
+use relative_path::RelativePathBuf;
+use serde::de::DeserializeOwned;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// kind of a resource in a resource file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    ColorSpace,
+    DrawParam,
+    Font,
+    MultiMedia,
+    CompositeGraphicUnit,
+}
+
+/// what an `ID` refers to in a document
+///
+/// paths are the resolved paths of the xml files holding the element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdTarget {
+    Page {
+        page_index: usize,
+    },
+    Template {
+        path: RelativePathBuf,
+    },
+    Layer {
+        path: RelativePathBuf,
+    },
+    /// an object on a page or a template, `path` is the content xml
+    Object {
+        path: RelativePathBuf,
+    },
+    Resource {
+        path: RelativePathBuf,
+        kind: ResourceKind,
+    },
+    Annotation {
+        path: RelativePathBuf,
+    },
+    /// an object in the appearance of an annotation
+    AppearanceObject {
+        path: RelativePathBuf,
+    },
+}
+
+/// all the `ID`s in a document
+///
+/// built by [crate::Ofd::id_index], covering pages, templates, layers,
+/// page objects, resources and annotations.
+#[derive(Debug, Clone, Default)]
+pub struct IdIndex {
+    max_unit_id: StId,
+    ids: HashMap<StId, IdTarget>,
+    /// occurrences after the first one
+    duplicates: Vec<(StId, IdTarget)>,
+}
+
+impl IdIndex {
+    pub(crate) fn new(max_unit_id: StId) -> Self {
+        Self {
+            max_unit_id,
+            ..Default::default()
+        }
+    }
+
+    /// `CommonData.MaxUnitID` of the document
+    pub fn max_unit_id(&self) -> StId {
+        self.max_unit_id
+    }
+
+    /// the first element using `id`
+    pub fn get(&self, id: StId) -> Option<&IdTarget> {
+        self.ids.get(&id)
+    }
+
+    pub fn contains(&self, id: StId) -> bool {
+        self.ids.contains_key(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// all the unique ids, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (StId, &IdTarget)> {
+        self.ids.iter().map(|(id, t)| (*id, t))
+    }
+
+    /// index of a page by its `ID`
+    pub fn page_index_by_id(&self, page_id: StId) -> Option<usize> {
+        match self.ids.get(&page_id) {
+            Some(IdTarget::Page { page_index }) => Some(*page_index),
+            _ => None,
+        }
+    }
+
+    /// elements reusing an `ID` already taken, in document order
+    pub fn duplicates(&self) -> &[(StId, IdTarget)] {
+        &self.duplicates
+    }
+
+    /// ids greater than `MaxUnitID`, sorted
+    pub fn exceeding_max_unit_id(&self) -> Vec<StId> {
+        let mut ids = self
+            .ids
+            .keys()
+            .copied()
+            .filter(|id| *id > self.max_unit_id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    pub(crate) fn insert(&mut self, id: StId, target: IdTarget) {
+        match self.ids.entry(id) {
+            Entry::Occupied(_) => self.duplicates.push((id, target)),
+            Entry::Vacant(e) => {
+                e.insert(target);
+            }
+        }
+    }
+
+    /// layers and objects in the content of a page or template
+    pub(crate) fn insert_layers(&mut self, layers: &[Layer], path: &RelativePathBuf) {
+        for layer in layers {
+            self.insert(layer.id, IdTarget::Layer { path: path.clone() });
+            self.insert_objects(layer.objects.as_deref(), &|| IdTarget::Object {
+                path: path.clone(),
+            });
+        }
+    }
+
+    /// objects and the objects in page blocks
+    pub(crate) fn insert_objects(
+        &mut self,
+        objects: Option<&[VtGraphicUnit]>,
+        target: &dyn Fn() -> IdTarget,
+    ) {
+        for object in objects.into_iter().flatten() {
+            let id = match object {
+                VtGraphicUnit::TextObject(t) => t.id,
+                VtGraphicUnit::PathObject(p) => p.id,
+                VtGraphicUnit::ImageObject(i) => i.id,
+                VtGraphicUnit::CompositeObject(c) => c.id,
+                VtGraphicUnit::PageBlock(b) => {
+                    self.insert(b.id, target());
+                    self.insert_objects(b.objects.as_deref(), target);
+                    continue;
+                }
+            };
+            self.insert(id, target());
+        }
+    }
+
+    /// resources defined in a resource file
+    pub(crate) fn insert_resources(&mut self, resources: &[Resource], path: &RelativePathBuf) {
+        let mut insert = |id, kind| {
+            let path = path.clone();
+            self.insert(id, IdTarget::Resource { path, kind });
+        };
+        for resource in resources {
+            match resource {
+                Resource::ColorSpaces(r) => r
+                    .color_spaces
+                    .iter()
+                    .for_each(|r| insert(r.id, ResourceKind::ColorSpace)),
+                Resource::DrawParams(r) => r
+                    .draw_params
+                    .iter()
+                    .for_each(|r| insert(r.id, ResourceKind::DrawParam)),
+                Resource::Fonts(r) => r
+                    .fonts
+                    .iter()
+                    .for_each(|r| insert(r.id, ResourceKind::Font)),
+                Resource::MultiMedias(r) => r
+                    .multi_medias
+                    .iter()
+                    .for_each(|r| insert(r.id, ResourceKind::MultiMedia)),
+                Resource::CompositeGraphicUnits(r) => r
+                    .composite_graphic_units
+                    .iter()
+                    .for_each(|r| insert(r.id, ResourceKind::CompositeGraphicUnit)),
+            }
+        }
+    }
+}
+
+/// reads the items of a document for [index_document]
+///
+/// returning `Ok(None)` skips the item, errors stop the indexing.
+pub(crate) trait IndexLoader {
+    /// resolve `loc` found in `parent`, `name` is the element or attribute holding it
+    fn locate(
+        &mut self,
+        parent: &RelativePathBuf,
+        name: &'static str,
+        loc: &StLoc,
+    ) -> Result<Option<RelativePathBuf>>;
+
+    fn load<T>(&mut self, path: &RelativePathBuf) -> Result<Option<Rc<T>>>
+    where
+        T: 'static + DeserializeOwned;
+}
+
+/// a document indexed by [index_document], with the items read for it
+pub(crate) struct IndexedDocument {
+    pub index: IdIndex,
+    /// templates then pages
+    pub pages: Vec<(RelativePathBuf, Rc<PageXmlFile>)>,
+    pub resources: Vec<(RelativePathBuf, Rc<ResourceXmlFile>)>,
+    pub annotations: Option<(RelativePathBuf, Rc<AnnotationsXmlFile>)>,
+    pub annotation_files: Vec<(RelativePathBuf, Rc<AnnotationXmlFile>)>,
+}
+
+/// collect the ids of pages, templates, resources and annotations of a document
+pub(crate) fn index_document(
+    doc_path: &RelativePathBuf,
+    doc: &DocumentXmlFile,
+    loader: &mut impl IndexLoader,
+) -> Result<IndexedDocument> {
+    let common_data = &doc.common_data;
+    let mut index = IdIndex::new(common_data.max_unit_id);
+    for (page_index, page) in doc.pages.page.iter().enumerate() {
+        index.insert(page.id, IdTarget::Page { page_index });
+    }
+
+    // resource files may be shared by pages
+    let mut res_paths = vec![];
+    let doc_res = common_data
+        .public_res
+        .iter()
+        .chain(&common_data.document_res);
+    for loc in doc_res.flatten() {
+        if let Some(path) = loader.locate(doc_path, "Res", loc)? {
+            res_paths.push(path);
+        }
+    }
+
+    let mut pages = vec![];
+    for tpl in common_data.template_page.iter().flatten() {
+        let Some(path) = loader.locate(doc_path, "BaseLoc", &tpl.base_loc)? else {
+            continue;
+        };
+        if let Some(xml) = loader.load::<PageXmlFile>(&path)? {
+            index.insert(tpl.id, IdTarget::Template { path: path.clone() });
+            pages.push((path, xml));
+        }
+    }
+    for page in doc.pages.page.iter() {
+        let Some(path) = loader.locate(doc_path, "BaseLoc", &page.base_loc)? else {
+            continue;
+        };
+        let Some(xml) = loader.load::<PageXmlFile>(&path)? else {
+            continue;
+        };
+        for loc in xml.page_res.iter().flatten() {
+            match loader.locate(&path, "PageRes", loc)? {
+                Some(p) if !res_paths.contains(&p) => res_paths.push(p),
+                _ => {}
+            }
+        }
+        pages.push((path, xml));
+    }
+    for (path, xml) in pages.iter() {
+        if let Some(content) = &xml.content {
+            index.insert_layers(&content.layer, path);
+        }
+    }
+
+    let mut resources = vec![];
+    for path in res_paths {
+        if let Some(xml) = loader.load::<ResourceXmlFile>(&path)? {
+            index.insert_resources(xml.resources.as_deref().unwrap_or_default(), &path);
+            resources.push((path, xml));
+        }
+    }
+
+    let mut annotations = None;
+    let mut annotation_files = vec![];
+    if let Some(loc) = &doc.annotations {
+        if let Some(path) = loader.locate(doc_path, "Annotations", loc)? {
+            annotations = loader
+                .load::<AnnotationsXmlFile>(&path)?
+                .map(|xml| (path, xml));
+        }
+    }
+    for (annots_path, xml) in annotations.iter() {
+        for page in xml.page.iter().flatten() {
+            let Some(path) = loader.locate(annots_path, "FileLoc", &page.file_loc)? else {
+                continue;
+            };
+            let Some(annots) = loader.load::<AnnotationXmlFile>(&path)? else {
+                continue;
+            };
+            for annot in annots.annot.iter() {
+                index.insert(annot.id, IdTarget::Annotation { path: path.clone() });
+                index.insert_objects(annot.appearance.objects.as_deref(), &|| {
+                    IdTarget::AppearanceObject { path: path.clone() }
+                });
+            }
+            annotation_files.push((path, annots));
+        }
+    }
+
+    Ok(IndexedDocument {
+        index,
+        pages,
+        resources,
+        annotations,
+        annotation_files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicates_and_max_unit_id() {
+        let path = RelativePathBuf::from("Doc_0/Pages/Page_0/Content.xml");
+        let mut index = IdIndex::new(10);
+        index.insert(1, IdTarget::Page { page_index: 0 });
+        index.insert(2, IdTarget::Object { path: path.clone() });
+        index.insert(12, IdTarget::Layer { path: path.clone() });
+        index.insert(2, IdTarget::Layer { path: path.clone() });
+        index.insert(11, IdTarget::Object { path: path.clone() });
+
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.page_index_by_id(1), Some(0));
+        assert_eq!(index.page_index_by_id(2), None);
+        assert_eq!(index.get(2), Some(&IdTarget::Object { path: path.clone() }));
+        assert_eq!(index.duplicates(), &[(2, IdTarget::Layer { path })]);
+        assert_eq!(index.exceeding_max_unit_id(), vec![11, 12]);
+    }
+}
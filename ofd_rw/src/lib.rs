@@ -1,9 +1,38 @@
+mod cache;
 mod container;
+mod custom_tag;
+mod editor;
 pub mod error;
+mod extract;
+mod id_index;
+mod limits;
+mod merge;
+mod outline;
+mod page_edit;
+mod renumber;
+mod repair;
+mod schema;
+mod shared;
+mod storage;
+mod validate;
+mod writer;
 
 pub use container::*;
+pub use custom_tag::*;
+pub use editor::*;
+pub use extract::*;
+pub use id_index::*;
+pub use limits::*;
+pub use merge::*;
+pub use outline::*;
+pub use page_edit::*;
+pub use repair::*;
+pub use schema::*;
+pub use shared::*;
+pub use storage::*;
+pub use validate::*;
+pub use writer::*;
 
-#[allow(unused)]
 const OFD_NS: &str = "http://www.ofdspec.org/2016";
 
 #[cfg(test)]
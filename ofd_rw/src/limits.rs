@@ -0,0 +1,158 @@
+use crate::error::{Error, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+/// safety limits for reading untrusted packages, see [crate::Ofd::set_limits]
+///
+/// `None` means no limit, which is the default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// max uncompressed size of an item
+    pub max_item_size: Option<u64>,
+    /// max ratio of the uncompressed size to the compressed size of an item,
+    /// only checked for zip packages
+    pub max_compression_ratio: Option<u64>,
+    /// max number of items in the package
+    pub max_items: Option<usize>,
+    /// max bytes read from all the items, items read again are counted again
+    pub max_total_size: Option<u64>,
+    /// max nesting depth of the elements in an xml item
+    pub max_xml_depth: Option<usize>,
+    /// max size of a package loaded into memory, see [crate::SharedOfd]
+    pub max_package_size: Option<u64>,
+}
+
+impl Limits {
+    /// limits for packages from untrusted sources, such as uploads
+    pub fn untrusted() -> Self {
+        Self {
+            max_item_size: Some(64 * 1024 * 1024),
+            max_compression_ratio: Some(100),
+            max_items: Some(10_000),
+            max_total_size: Some(512 * 1024 * 1024),
+            max_xml_depth: Some(64),
+            max_package_size: Some(512 * 1024 * 1024),
+        }
+    }
+
+    pub(crate) fn check_package(&self, size: u64) -> Result<()> {
+        match self.max_package_size {
+            Some(max) if size > max => Err(Error::PackageTooLarge { size, max }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_items(&self, count: usize) -> Result<()> {
+        match self.max_items {
+            Some(max) if count > max => Err(Error::TooManyItems { count, max }),
+            _ => Ok(()),
+        }
+    }
+
+    /// `read` bytes of the item at `path` so far, `total` bytes of all the items
+    pub(crate) fn check_read(
+        &self,
+        path: &str,
+        read: u64,
+        compressed: Option<u64>,
+        total: u64,
+    ) -> Result<()> {
+        if let Some(max) = self.max_item_size.filter(|max| read > *max) {
+            return Err(Error::ItemTooLarge {
+                path: path.into(),
+                max,
+            });
+        }
+        if let (Some(max), Some(compressed)) = (self.max_compression_ratio, compressed) {
+            if read / compressed.max(1) > max {
+                return Err(Error::CompressionRatioExceeded {
+                    path: path.into(),
+                    max,
+                });
+            }
+        }
+        if let Some(max) = self.max_total_size.filter(|max| total > *max) {
+            return Err(Error::TotalSizeExceeded {
+                path: path.into(),
+                max,
+            });
+        }
+        Ok(())
+    }
+
+    /// scan the nesting depth of an xml item before building the tree,
+    /// syntax errors are left to the parser
+    pub(crate) fn check_xml(&self, path: &str, xml: &[u8]) -> Result<()> {
+        let Some(max) = self.max_xml_depth else {
+            return Ok(());
+        };
+        let mut reader = Reader::from_reader(xml);
+        let mut buf = Vec::new();
+        let mut depth = 0_usize;
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(_)) => {
+                    depth += 1;
+                    if depth > max {
+                        return Err(Error::XmlTooDeep {
+                            path: path.into(),
+                            max,
+                        });
+                    }
+                }
+                Ok(Event::End(_)) => depth = depth.saturating_sub(1),
+                Ok(Event::Eof) | Err(_) => return Ok(()),
+                Ok(_) => {}
+            }
+            buf.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_read() {
+        let limits = Limits {
+            max_item_size: Some(100),
+            max_compression_ratio: Some(10),
+            max_total_size: Some(1000),
+            ..Default::default()
+        };
+        assert!(limits.check_read("a", 100, Some(10), 1000).is_ok());
+        assert!(limits.check_read("a", 100, None, 1000).is_ok());
+        assert!(matches!(
+            limits.check_read("a", 101, None, 101),
+            Err(Error::ItemTooLarge { max: 100, .. })
+        ));
+        assert!(matches!(
+            limits.check_read("a", 100, Some(9), 100),
+            Err(Error::CompressionRatioExceeded { max: 10, .. })
+        ));
+        assert!(matches!(
+            limits.check_read("a", 10, None, 1001),
+            Err(Error::TotalSizeExceeded { max: 1000, .. })
+        ));
+        assert!(Limits::default()
+            .check_read("a", u64::MAX, Some(0), u64::MAX)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_check_xml() {
+        let limits = Limits {
+            max_xml_depth: Some(2),
+            ..Default::default()
+        };
+        assert!(limits.check_xml("a", b"<a><b/><b></b></a>").is_ok());
+        assert!(limits.check_xml("a", b"<a><b><c/></b></a>").is_ok());
+        assert!(matches!(
+            limits.check_xml("a", b"<a><b><c></c></b></a>"),
+            Err(Error::XmlTooDeep { max: 2, .. })
+        ));
+        // left to the parser
+        assert!(limits.check_xml("a", b"<a><b></a>").is_ok());
+    }
+}
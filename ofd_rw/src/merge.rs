@@ -0,0 +1,620 @@
+use crate::container::{inner_resolve, Ofd};
+use crate::error::{Error, Result};
+use crate::renumber::{IdVisitor, Renumber, VisitIds};
+use crate::storage::Storage;
+use crate::writer::OfdWriter;
+use ofd_base::file::annotation::{AnnotationXmlFile, AnnotationsXmlFile, Page as AnnotPage};
+use ofd_base::file::document::{
+    Bookmarks, CommonData, CtBookmark, CtOutlineElem, CtPageArea, DocumentXmlFile, Outlines, Page,
+    TemplatePage,
+};
+use ofd_base::file::ofd::{CtDocInfo, DocBody, OfdXmlFile};
+use ofd_base::file::page::PageXmlFile;
+use ofd_base::file::res::{
+    ColorSpace, ColorSpaces, CompositeGraphicUnit, CompositeGraphicUnits, DrawParam, DrawParams,
+    Font, Fonts, MultiMedia, MultiMediaType, MultiMedias, Resource, ResourceXmlFile,
+};
+use ofd_base::{StId, StLoc};
+use relative_path::RelativePathBuf;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufWriter, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// fonts with the same attributes and font file are merged into one
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FontKey {
+    font_name: String,
+    family_name: Option<String>,
+    charset: Option<String>,
+    italic: Option<bool>,
+    bold: Option<bool>,
+    serif: Option<bool>,
+    fixed_width: Option<bool>,
+    font_file: Option<StLoc>,
+}
+
+/// so are multimedia with the same type, format and media file
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MediaKey {
+    r#type: MultiMediaType,
+    format: Option<String>,
+    media_file: StLoc,
+}
+
+/// combines the documents of several packages into a single document
+///
+/// pages are appended in the order the documents are added.
+/// every `ID` is given a new number, so nothing collides,
+/// and identical fonts and images are stored once.
+/// outlines and bookmarks are kept, document level settings like
+/// `Permissions` come from the first document.
+/// signatures, attachments, custom tags, extensions and versions
+/// are left out, as they do not survive a merge.
+#[derive(Debug, Default)]
+pub struct OfdMerger {
+    doc_info: Option<CtDocInfo>,
+    version: Option<String>,
+    document: Option<DocumentXmlFile>,
+    /// the next `ID` to give out
+    next_id: StId,
+    pages: Vec<PageXmlFile>,
+    templates: Vec<PageXmlFile>,
+    /// with the `ID` of the page they belong to
+    annotations: Vec<(StId, AnnotationXmlFile)>,
+    outlines: Vec<CtOutlineElem>,
+    bookmarks: Vec<CtBookmark>,
+    color_spaces: Vec<ColorSpace>,
+    draw_params: Vec<DrawParam>,
+    fonts: Vec<Font>,
+    multi_medias: Vec<MultiMedia>,
+    composite_graphic_units: Vec<CompositeGraphicUnit>,
+    font_ids: HashMap<FontKey, StId>,
+    media_ids: HashMap<MediaKey, StId>,
+    /// resource files by name under `Res`
+    files: Vec<(String, Vec<u8>)>,
+    file_hashes: HashMap<u64, Vec<usize>>,
+}
+
+impl OfdMerger {
+    pub fn new() -> Self {
+        Self {
+            next_id: 1,
+            ..Default::default()
+        }
+    }
+
+    /// add all the documents of a package
+    pub fn add<S: Storage>(&mut self, ofd: &Ofd<S>) -> Result<()> {
+        for doc_index in 0..ofd.entry()?.doc_body.len() {
+            self.add_document(ofd, doc_index)?;
+        }
+        Ok(())
+    }
+
+    /// add a document of a package
+    pub fn add_document<S: Storage>(&mut self, ofd: &Ofd<S>, doc_index: usize) -> Result<()> {
+        let doc = ofd.document_by_index(doc_index)?;
+        let mut renumber = Renumber::new(self.next_id.max(1));
+        renumber.set_default_cs(doc.common_data.default_cs);
+
+        // resources first, merged fonts and images take the id given before
+        let mut res_paths = vec![];
+        let common_data = &doc.common_data;
+        for loc in common_data
+            .public_res
+            .iter()
+            .chain(&common_data.document_res)
+        {
+            for loc in loc.iter() {
+                res_paths.push(doc.resolve(loc)?);
+            }
+        }
+        let mut tpls = vec![];
+        for tpl in common_data.template_page.iter().flatten() {
+            let path = doc.resolve(&tpl.base_loc)?;
+            let content = ofd.xml_by_path::<PageXmlFile>(path.as_str())?;
+            for loc in content.page_res.iter().flatten() {
+                res_paths.push(inner_resolve(&path, loc)?);
+            }
+            tpls.push((tpl, Rc::unwrap_or_clone(content)));
+        }
+        let mut pages = vec![];
+        for page_index in 0..doc.pages.page.len() {
+            let page = ofd.page_by_index(doc_index, page_index)?;
+            for loc in page.page_res.iter().flatten() {
+                res_paths.push(page.resolve(loc)?);
+            }
+            pages.push(page);
+        }
+        let mut seen = HashSet::new();
+        for path in res_paths {
+            if seen.insert(path.clone()) {
+                let res = ofd.xml_by_path::<ResourceXmlFile>(path.as_str())?;
+                self.add_resources(ofd, &path, Rc::unwrap_or_clone(res), &mut renumber)?;
+            }
+        }
+
+        let page_area = &self.document.as_ref().unwrap_or(&doc).common_data.page_area;
+        let page_area = page_area.clone();
+        let mut templates = vec![];
+        for (tpl, mut content) in tpls {
+            content.page_res = None;
+            fill_area(&mut content, &doc.common_data.page_area, &page_area);
+            content.visit_ids(&mut renumber);
+            templates.push(TemplatePage {
+                id: renumber.get(tpl.id),
+                name: tpl.name.clone(),
+                z_order: tpl.z_order.clone(),
+                base_loc: PathBuf::from(format!("Tpls/Tpl_{}/Content.xml", self.templates.len())),
+            });
+            self.templates.push(content);
+        }
+
+        let mut page_refs = vec![];
+        for (page_index, page) in pages.into_iter().enumerate() {
+            let page_id = doc.pages.page[page_index].id;
+            for annot in ofd.annotations_for_page(doc_index, page_index)? {
+                let mut content = Rc::unwrap_or_clone(annot.content);
+                content.visit_ids(&mut renumber);
+                self.annotations.push((renumber.get(page_id), content));
+            }
+            let mut content = Rc::unwrap_or_clone(page.content);
+            // moved into the resources of the merged document
+            content.page_res = None;
+            fill_area(&mut content, &doc.common_data.page_area, &page_area);
+            content.visit_ids(&mut renumber);
+            page_refs.push(Page {
+                id: renumber.get(page_id),
+                base_loc: PathBuf::from(format!("Pages/Page_{}/Content.xml", self.pages.len())),
+            });
+            self.pages.push(content);
+        }
+
+        // outlines and bookmarks, with the names taken renamed
+        let mut content = Rc::unwrap_or_clone(doc.content);
+        let names = self
+            .bookmarks
+            .iter()
+            .map(|b| b.name.clone())
+            .collect::<HashSet<_>>();
+        for bookmark in content.bookmarks.iter().flat_map(|b| b.bookmarks.iter()) {
+            if names.contains(&bookmark.name) {
+                let new_name = (2..)
+                    .map(|i| format!("{} ({i})", bookmark.name))
+                    .find(|n| !names.contains(n))
+                    .unwrap_or_default();
+                renumber.rename_bookmark(bookmark.name.clone(), new_name);
+            }
+        }
+        content.visit_ids(&mut renumber);
+        let outlines = content.outlines.take().into_iter();
+        self.outlines.extend(outlines.flat_map(|o| o.outline_elems));
+        let bookmarks = content.bookmarks.take().into_iter();
+        self.bookmarks.extend(bookmarks.flat_map(|b| b.bookmarks));
+
+        match &mut self.document {
+            Some(document) => {
+                let common_data = &mut document.common_data;
+                common_data
+                    .template_page
+                    .get_or_insert_with(Vec::new)
+                    .extend(templates);
+                document.pages.page.extend(page_refs);
+            }
+            None => {
+                let entry = ofd.entry()?;
+                let doc_body = &entry.doc_body[doc_index];
+                self.doc_info = Some(doc_body.doc_info.clone());
+                self.version = Some(entry.version.clone());
+                content.common_data.template_page = Some(templates);
+                content.pages.page = page_refs;
+                self.document = Some(content);
+            }
+        }
+        self.next_id = renumber.next();
+        Ok(())
+    }
+
+    /// number of pages added
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// write the merged package into `writer`
+    pub fn write_to<W: Write + Seek>(&self, writer: W) -> Result<W> {
+        let Some(document) = &self.document else {
+            return Err(Error::NothingToMerge);
+        };
+        let mut writer = OfdWriter::new(writer);
+        let entry = OfdXmlFile {
+            version: self.version.clone().unwrap_or_else(|| "1.1".into()),
+            doc_type: "OFD".into(),
+            doc_body: vec![DocBody {
+                doc_info: self.doc_info.clone().unwrap_or_default(),
+                doc_root: Some("Doc_0/Document.xml".into()),
+                versions: None,
+                signatures: None,
+            }],
+        };
+        writer.write_entry(&entry)?;
+
+        let resources = self.resources();
+        let document = DocumentXmlFile {
+            common_data: CommonData {
+                max_unit_id: self.next_id - 1,
+                page_area: document.common_data.page_area.clone(),
+                public_res: (!resources.is_empty()).then(|| vec!["PublicRes.xml".into()]),
+                document_res: None,
+                template_page: (!self.templates.is_empty())
+                    .then(|| document.common_data.template_page.clone())
+                    .flatten(),
+                default_cs: None,
+            },
+            pages: document.pages.clone(),
+            outlines: (!self.outlines.is_empty()).then(|| Outlines {
+                outline_elems: self.outlines.clone(),
+            }),
+            permissions: document.permissions.clone(),
+            actions: document.actions.clone(),
+            v_preferences: document.v_preferences.clone(),
+            bookmarks: (!self.bookmarks.is_empty()).then(|| Bookmarks {
+                bookmarks: self.bookmarks.clone(),
+            }),
+            annotations: (!self.annotations.is_empty()).then(|| "Annots/Annotations.xml".into()),
+            custom_tags: None,
+            attachments: None,
+            extensions: None,
+        };
+        writer.write_xml("Doc_0/Document.xml", &document)?;
+
+        if !resources.is_empty() {
+            let res = ResourceXmlFile {
+                base_loc: "Res".into(),
+                resources: Some(resources),
+            };
+            writer.write_xml("Doc_0/PublicRes.xml", &res)?;
+        }
+        for (name, bytes) in self.files.iter() {
+            writer.write_bytes(format!("Doc_0/Res/{name}"), bytes)?;
+        }
+        for (tpl, page) in document
+            .common_data
+            .template_page
+            .iter()
+            .flatten()
+            .zip(&self.templates)
+        {
+            let path = Path::new("Doc_0").join(&tpl.base_loc);
+            writer.write_xml(path.to_string_lossy(), page)?;
+        }
+        for (page_ref, page) in document.pages.page.iter().zip(&self.pages) {
+            let path = Path::new("Doc_0").join(&page_ref.base_loc);
+            writer.write_xml(path.to_string_lossy(), page)?;
+        }
+
+        if !self.annotations.is_empty() {
+            let mut pages = vec![];
+            for (i, (page_id, annot)) in self.annotations.iter().enumerate() {
+                let file_loc = format!("Annot_{i}.xml");
+                writer.write_xml(format!("Doc_0/Annots/{file_loc}"), annot)?;
+                pages.push(AnnotPage {
+                    page_id: *page_id,
+                    file_loc: file_loc.into(),
+                });
+            }
+            let annotations = AnnotationsXmlFile { page: Some(pages) };
+            writer.write_xml("Doc_0/Annots/Annotations.xml", &annotations)?;
+        }
+        writer.finish()
+    }
+
+    /// write the merged package to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = File::create(path)?;
+        let mut w = self.write_to(BufWriter::new(file))?;
+        w.flush()?;
+        Ok(())
+    }
+
+    fn add_resources<S: Storage>(
+        &mut self,
+        ofd: &Ofd<S>,
+        path: &RelativePathBuf,
+        res: ResourceXmlFile,
+        renumber: &mut Renumber,
+    ) -> Result<()> {
+        let base_loc = res.base_loc;
+        let mut copy_file = |loc: &StLoc| -> Result<StLoc> {
+            let file_path = inner_resolve(path, &base_loc.join(loc))?;
+            let bytes = ofd.bytes(file_path.as_str())?;
+            let name = loc.file_name().map(|n| n.to_string_lossy().into_owned());
+            Ok(self.add_file(name.unwrap_or_default(), bytes).into())
+        };
+        let mut resources = res.resources.unwrap_or_default();
+        for resource in resources.iter_mut() {
+            match resource {
+                Resource::ColorSpaces(r) => {
+                    for cs in r.color_spaces.iter_mut() {
+                        if let Some(profile) = &cs.profile {
+                            cs.profile = Some(copy_file(profile)?);
+                        }
+                    }
+                }
+                Resource::Fonts(r) => {
+                    for font in r.fonts.iter_mut() {
+                        if let Some(font_file) = &font.font_file {
+                            font.font_file = Some(copy_file(font_file)?);
+                        }
+                    }
+                }
+                Resource::MultiMedias(r) => {
+                    for media in r.multi_medias.iter_mut() {
+                        media.media_file = copy_file(&media.media_file)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for mut resource in resources {
+            match &mut resource {
+                Resource::Fonts(r) => {
+                    for mut font in std::mem::take(&mut r.fonts) {
+                        let key = FontKey {
+                            font_name: font.font_name.clone(),
+                            family_name: font.family_name.clone(),
+                            charset: font.charset.clone(),
+                            italic: font.italic,
+                            bold: font.bold,
+                            serif: font.serif,
+                            fixed_width: font.fixed_width,
+                            font_file: font.font_file.clone(),
+                        };
+                        match self.font_ids.get(&key) {
+                            Some(id) => renumber.alias(font.id, *id),
+                            None => {
+                                renumber.id(&mut font.id);
+                                self.font_ids.insert(key, font.id);
+                                self.fonts.push(font);
+                            }
+                        }
+                    }
+                }
+                Resource::MultiMedias(r) => {
+                    for mut media in std::mem::take(&mut r.multi_medias) {
+                        let key = MediaKey {
+                            r#type: media.r#type.clone(),
+                            format: media.format.clone(),
+                            media_file: media.media_file.clone(),
+                        };
+                        match self.media_ids.get(&key) {
+                            Some(id) => renumber.alias(media.id, *id),
+                            None => {
+                                renumber.id(&mut media.id);
+                                self.media_ids.insert(key, media.id);
+                                self.multi_medias.push(media);
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    resource.visit_ids(renumber);
+                    match resource {
+                        Resource::ColorSpaces(r) => self.color_spaces.extend(r.color_spaces),
+                        Resource::DrawParams(r) => self.draw_params.extend(r.draw_params),
+                        Resource::CompositeGraphicUnits(r) => self
+                            .composite_graphic_units
+                            .extend(r.composite_graphic_units),
+                        _ => unreachable!(),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// keep the bytes of a resource file once, returns its name under `Res`
+    fn add_file(&mut self, name: String, bytes: Vec<u8>) -> String {
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        let hash = hasher.finish();
+        let same = self.file_hashes.get(&hash).into_iter().flatten();
+        if let Some(i) = same.copied().find(|i| self.files[*i].1 == bytes) {
+            return self.files[i].0.clone();
+        }
+        let taken = |n: &str| self.files.iter().any(|(name, _)| name == n);
+        let name = match taken(&name) || name.is_empty() {
+            true => format!("{}_{name}", self.files.len()),
+            false => name,
+        };
+        self.file_hashes
+            .entry(hash)
+            .or_default()
+            .push(self.files.len());
+        self.files.push((name.clone(), bytes));
+        name
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        let mut resources = vec![];
+        if !self.color_spaces.is_empty() {
+            resources.push(Resource::ColorSpaces(ColorSpaces {
+                color_spaces: self.color_spaces.clone(),
+            }));
+        }
+        if !self.draw_params.is_empty() {
+            resources.push(Resource::DrawParams(DrawParams {
+                draw_params: self.draw_params.clone(),
+            }));
+        }
+        if !self.fonts.is_empty() {
+            resources.push(Resource::Fonts(Fonts {
+                fonts: self.fonts.clone(),
+            }));
+        }
+        if !self.multi_medias.is_empty() {
+            resources.push(Resource::MultiMedias(MultiMedias {
+                multi_medias: self.multi_medias.clone(),
+            }));
+        }
+        if !self.composite_graphic_units.is_empty() {
+            resources.push(Resource::CompositeGraphicUnits(CompositeGraphicUnits {
+                composite_graphic_units: self.composite_graphic_units.clone(),
+            }));
+        }
+        resources
+    }
+}
+
+/// pages and templates relying on the page area of their document
+/// keep it, when it differs from the one of the merged document
+fn fill_area(page: &mut PageXmlFile, area: &CtPageArea, merged: &CtPageArea) {
+    if page.area.is_none() && area.physical_box != merged.physical_box {
+        page.area = Some(area.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::OfdEditor;
+    use crate::validate::IssueKind;
+    use crate::NavTarget;
+    use crate::{from_bytes, from_path};
+    use eyre::Result;
+    use ofd_base::common::{ActionType, Actions, CtAction, CtDest, Event, VtTo};
+    use std::io::Cursor;
+    use zip::ZipArchive;
+
+    /// sample 000 with an outline and a bookmark to its page
+    fn with_outline() -> Result<Ofd<ZipArchive<Cursor<Vec<u8>>>>> {
+        let ofd = from_path("../samples/000.ofd")?;
+        let mut editor = OfdEditor::new(ofd);
+        let document = editor.document_mut(0)?;
+        let dest = CtDest {
+            r#type: "XYZ".into(),
+            page_id: document.pages.page[0].id,
+            left: Some(0.0),
+            right: None,
+            top: Some(0.0),
+            bottom: None,
+            zoom: None,
+        };
+        let goto = |value| Actions {
+            actions: vec![CtAction {
+                event: Event::Click,
+                region: None,
+                action_type: ActionType::Goto { value },
+            }],
+        };
+        document.outlines = Some(Outlines {
+            outline_elems: vec![CtOutlineElem {
+                title: "invoice".into(),
+                count: None,
+                expanded: None,
+                actions: Some(goto(VtTo::Dest(dest.clone()))),
+                outline_elems: None,
+            }],
+        });
+        document.bookmarks = Some(Bookmarks {
+            bookmarks: vec![CtBookmark {
+                name: "top".into(),
+                dest,
+            }],
+        });
+        let out = editor.save_to(Cursor::new(Vec::new()))?.into_inner();
+        Ok(from_bytes(out)?)
+    }
+
+    fn pages<S: Storage>(ofd: &Ofd<S>) -> Result<usize> {
+        Ok(ofd.document_by_index(0)?.pages.page.len())
+    }
+
+    #[test]
+    fn test_merge() -> Result<()> {
+        let first = with_outline()?;
+        let second = from_path("../samples/001.ofd")?;
+        let mut merger = OfdMerger::new();
+        merger.add(&first)?;
+        merger.add(&second)?;
+        merger.add(&first)?;
+        let count = pages(&first)? * 2 + second.document_by_index(0)?.pages.page.len();
+        assert_eq!(merger.page_count(), count);
+
+        let out = merger.write_to(Cursor::new(Vec::new()))?.into_inner();
+        let merged = from_bytes(out)?;
+        assert_eq!(merged.entry()?.doc_body.len(), 1);
+        assert_eq!(pages(&merged)?, count);
+        // only the draw params sample 001 refers to without defining them
+        let issues = merged.validate()?;
+        assert_eq!(issues.len(), second.validate()?.len());
+        assert!(issues.iter().all(|i| matches!(
+            i.kind,
+            IssueKind::MissingId {
+                name: "DrawParam",
+                ..
+            }
+        )));
+
+        // each document keeps its outline, pointing to its own first page
+        let outlines = merged.outlines_for_doc(0)?;
+        assert_eq!(outlines.len(), 2);
+        let page_index = |i: usize| match &outlines[i].target {
+            Some(NavTarget::Page { page_index, .. }) => *page_index,
+            _ => usize::MAX,
+        };
+        assert_eq!(page_index(0), 0);
+        assert_eq!(page_index(1), count - pages(&first)?);
+        let bookmarks = merged.bookmarks_for_doc(0)?;
+        let names = bookmarks
+            .iter()
+            .map(|b| b.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["top", "top (2)"]);
+        assert_eq!(bookmarks[1].page_index, Some(page_index(1)));
+
+        // the second copy of sample 000 brings no new fonts or files
+        let res = merged.xml_by_path::<ResourceXmlFile>("Doc_0/PublicRes.xml")?;
+        let fonts = res.resources.iter().flatten().map(|r| match r {
+            Resource::Fonts(fonts) => fonts.fonts.len(),
+            _ => 0,
+        });
+        let mut once = OfdMerger::new();
+        once.add(&first)?;
+        once.add(&second)?;
+        assert_eq!(fonts.sum::<usize>(), once.fonts.len());
+        assert_eq!(merger.files.len(), once.files.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_ids() -> Result<()> {
+        let ofd = from_path("../samples/000.ofd")?;
+        let mut merger = OfdMerger::new();
+        merger.add(&ofd)?;
+        merger.add(&ofd)?;
+        let out = merger.write_to(Cursor::new(Vec::new()))?.into_inner();
+        let merged = from_bytes(out)?;
+        let document = merged.document_by_index(0)?;
+        let ids = document.pages.page.iter().map(|p| p.id).collect::<Vec<_>>();
+        assert_ne!(ids[0], ids[1]);
+        assert!(ids.iter().all(|id| *id <= document.common_data.max_unit_id));
+        let issues = merged.validate()?;
+        assert!(!issues.iter().any(|i| matches!(
+            i.kind,
+            IssueKind::DuplicateId { .. } | IssueKind::ExceedsMaxUnitId(_)
+        )));
+        Ok(())
+    }
+
+    #[test]
+    fn test_nothing_to_merge() {
+        let merger = OfdMerger::new();
+        let res = merger.write_to(Cursor::new(Vec::new()));
+        assert!(matches!(res, Err(Error::NothingToMerge)));
+    }
+}
@@ -0,0 +1,127 @@
+use ofd_base::common::{ActionType, Actions, CtDest, VtTo};
+use ofd_base::file::document::{CtOutlineElem, DocumentXmlFile};
+use ofd_base::StRefId;
+use std::collections::HashMap;
+
+/// page indices by page `ID`
+type PageIndices = HashMap<StRefId, usize>;
+
+fn page_indices(doc: &DocumentXmlFile) -> PageIndices {
+    let pages = doc.pages.page.iter().enumerate();
+    // the first page wins if an id is used twice
+    pages.rev().map(|(i, p)| (p.id, i)).collect()
+}
+
+/// where an outline or bookmark leads to
+#[derive(Debug, Clone)]
+pub enum NavTarget {
+    /// a position on a page, `dest` holds `Type`, `Left`, `Top`, `Zoom` and so on
+    Page {
+        page_index: usize,
+        dest: CtDest,
+    },
+    Uri {
+        uri: String,
+        base: Option<String>,
+    },
+    Attachment {
+        attach_id: String,
+    },
+}
+
+/// an entry of the flattened outline tree
+#[derive(Debug, Clone)]
+pub struct OutlineEntry {
+    pub title: String,
+    /// 0 for the top level
+    pub depth: usize,
+    /// default `true`
+    pub expanded: bool,
+    /// `None` if there is no action, or it points to nothing in the document
+    pub target: Option<NavTarget>,
+}
+
+/// a named bookmark with its destination resolved
+#[derive(Debug, Clone)]
+pub struct BookmarkEntry {
+    pub name: String,
+    pub page_index: Option<usize>,
+    pub dest: CtDest,
+}
+
+/// outlines of a document in depth first order
+pub(crate) fn flatten_outlines(doc: &DocumentXmlFile) -> Vec<OutlineEntry> {
+    let mut entries = vec![];
+    if let Some(outlines) = &doc.outlines {
+        let pages = page_indices(doc);
+        push_outlines(&outlines.outline_elems, 0, doc, &pages, &mut entries);
+    }
+    entries
+}
+
+fn push_outlines(
+    elems: &[CtOutlineElem],
+    depth: usize,
+    doc: &DocumentXmlFile,
+    pages: &PageIndices,
+    entries: &mut Vec<OutlineEntry>,
+) {
+    for elem in elems {
+        entries.push(OutlineEntry {
+            title: elem.title.clone(),
+            depth,
+            expanded: elem.expanded.unwrap_or(true),
+            target: elem
+                .actions
+                .as_ref()
+                .and_then(|a| resolve_actions(a, doc, pages)),
+        });
+        if let Some(children) = &elem.outline_elems {
+            push_outlines(children, depth + 1, doc, pages, entries);
+        }
+    }
+}
+
+pub(crate) fn bookmarks(doc: &DocumentXmlFile) -> Vec<BookmarkEntry> {
+    let pages = page_indices(doc);
+    doc.bookmarks
+        .iter()
+        .flat_map(|b| b.bookmarks.iter())
+        .map(|b| BookmarkEntry {
+            name: b.name.clone(),
+            page_index: pages.get(&b.dest.page_id).copied(),
+            dest: b.dest.clone(),
+        })
+        .collect()
+}
+
+/// the first action leading somewhere
+fn resolve_actions(
+    actions: &Actions,
+    doc: &DocumentXmlFile,
+    pages: &PageIndices,
+) -> Option<NavTarget> {
+    actions.actions.iter().find_map(|a| match &a.action_type {
+        ActionType::Goto { value } => {
+            let dest = match value {
+                VtTo::Dest(dest) => dest,
+                VtTo::Bookmark { name } => {
+                    let bookmarks = doc.bookmarks.as_ref()?;
+                    &bookmarks.bookmarks.iter().find(|b| &b.name == name)?.dest
+                }
+            };
+            Some(NavTarget::Page {
+                page_index: *pages.get(&dest.page_id)?,
+                dest: dest.clone(),
+            })
+        }
+        ActionType::Uri { uri, base } => Some(NavTarget::Uri {
+            uri: uri.clone(),
+            base: base.clone(),
+        }),
+        ActionType::GotoA { attach_id, .. } => Some(NavTarget::Attachment {
+            attach_id: attach_id.clone(),
+        }),
+        ActionType::Sound { .. } | ActionType::Movie { .. } => None,
+    })
+}
@@ -0,0 +1,564 @@
+use crate::container::{inner_resolve, RawOfd};
+use crate::editor::OfdEditor;
+use crate::error::{Error, Result};
+use crate::extract::ActionFilter;
+use crate::storage::Storage;
+use ofd_base::file::annotation::{AnnotationXmlFile, AnnotationsXmlFile};
+use ofd_base::file::document::{CtPageArea, DocumentXmlFile, Page, TemplatePage};
+use ofd_base::file::ofd::OfdXmlFile;
+use ofd_base::file::page::{Layer, PageXmlFile, VtGraphicUnit};
+use ofd_base::file::signature::{SignatureXmlFile, SignaturesXmlFile};
+use ofd_base::{StArray, StBox, StId};
+use relative_path::RelativePathBuf;
+use std::path::PathBuf;
+
+/// clockwise rotation of a page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageRotation {
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+impl<S: Storage> OfdEditor<S> {
+    /// put the pages of a document in a new order
+    ///
+    /// `order` lists the current page indices in their new order, each one once.
+    pub fn reorder_pages(&mut self, doc_index: usize, order: &[usize]) -> Result<()> {
+        let pages = &mut self.document_mut(doc_index)?.pages.page;
+        let mut sorted = order.to_vec();
+        sorted.sort_unstable();
+        if !sorted.into_iter().eq(0..pages.len()) {
+            return Err(Error::InvalidPageOrder);
+        }
+        *pages = order.iter().map(|i| pages[*i].clone()).collect();
+        Ok(())
+    }
+
+    /// remove a page, with its annotations and the stamps on it
+    ///
+    /// bookmarks, outlines and document actions leading to the page are removed too.
+    /// a signature losing a stamp no longer verifies.
+    pub fn delete_page(&mut self, doc_index: usize, page_index: usize) -> Result<()> {
+        let doc_path = self.document_path(doc_index)?;
+        let document = self.document_mut(doc_index)?;
+        if page_index >= document.pages.page.len() {
+            return Err(Error::NoSuchPage);
+        }
+        let page = document.pages.page.remove(page_index);
+        ActionFilter::prune(document);
+        let annotations = document.annotations.clone();
+        self.remove(inner_resolve(&doc_path, &page.base_loc)?);
+
+        if let Some(loc) = annotations {
+            let path = inner_resolve(&doc_path, &loc)?;
+            let current = self.current::<AnnotationsXmlFile>(path.as_str())?;
+            if current.page.iter().flatten().any(|p| p.page_id == page.id) {
+                let entries = &mut self.xml_mut::<AnnotationsXmlFile>(path.as_str())?.page;
+                let (removed, kept) = entries
+                    .take()
+                    .into_iter()
+                    .flatten()
+                    .partition::<Vec<_>, _>(|p| p.page_id == page.id);
+                *entries = Some(kept).filter(|k| !k.is_empty());
+                let kept = entries.clone().unwrap_or_default();
+                for entry in removed {
+                    // a file may be shared with another page
+                    if !kept.iter().any(|p| p.file_loc == entry.file_loc) {
+                        self.remove(inner_resolve(&path, &entry.file_loc)?);
+                    }
+                }
+            }
+        }
+
+        for path in self.signature_paths(doc_index)? {
+            let signature = self.current::<SignatureXmlFile>(path.as_str())?;
+            let mut stamps = signature.signed_info.stamp_annot.iter().flatten();
+            if stamps.any(|s| s.page_ref == page.id) {
+                let signature = self.xml_mut::<SignatureXmlFile>(path.as_str())?;
+                let stamps = &mut signature.signed_info.stamp_annot;
+                if let Some(stamps) = stamps {
+                    stamps.retain(|s| s.page_ref != page.id);
+                }
+                *stamps = stamps.take().filter(|s| !s.is_empty());
+            }
+        }
+        Ok(())
+    }
+
+    /// insert a blank page at `page_index`, the page count appends it
+    ///
+    /// returns the `ID` of the new page.
+    pub fn insert_blank_page(
+        &mut self,
+        doc_index: usize,
+        page_index: usize,
+        area: CtPageArea,
+    ) -> Result<StId> {
+        let doc_path = self.document_path(doc_index)?;
+        let base_loc = self.free_loc(&doc_path, "Pages/Page")?;
+        let document = self.document_mut(doc_index)?;
+        if page_index > document.pages.page.len() {
+            return Err(Error::NoSuchPage);
+        }
+        let id = new_id(document);
+        let page = Page {
+            id,
+            base_loc: base_loc.clone(),
+        };
+        document.pages.page.insert(page_index, page);
+        let page = PageXmlFile {
+            area: Some(area),
+            template: None,
+            page_res: None,
+            content: None,
+        };
+        self.put_xml(inner_resolve(&doc_path, &base_loc)?, page);
+        Ok(id)
+    }
+
+    /// turn a page clockwise
+    ///
+    /// the rotation is folded into the `Boundary` and `CTM` of every object
+    /// on the page, in its templates and its annotations, and the page area
+    /// turns with them. the templates are rotated as copies, so the other
+    /// pages using them are left as they are. stamps are not moved.
+    pub fn rotate_page(
+        &mut self,
+        doc_index: usize,
+        page_index: usize,
+        rotation: PageRotation,
+    ) -> Result<()> {
+        let doc_path = self.document_path(doc_index)?;
+        let document = self.current::<DocumentXmlFile>(doc_path.as_str())?;
+        let page_ref = document.pages.page.get(page_index);
+        let page_ref = page_ref.ok_or(Error::NoSuchPage)?;
+        let page_path = inner_resolve(&doc_path, &page_ref.base_loc)?;
+        let mut page = self.current::<PageXmlFile>(page_path.as_str())?;
+        let area = page
+            .area
+            .as_ref()
+            .unwrap_or(&document.common_data.page_area);
+        let transform = Transform::rotate(rotation, &area.physical_box);
+        page.area = Some(transform.area(area));
+        rotate_layers(&mut page, &transform);
+
+        let mut max_id = document.common_data.max_unit_id;
+        let mut new_templates = vec![];
+        for tpl_ref in page.template.iter_mut().flatten() {
+            let mut templates = document.common_data.template_page.iter().flatten();
+            let tpl = templates.find(|t| t.id == tpl_ref.template_id);
+            let tpl = tpl.ok_or(Error::NoSuchTemplate)?;
+            let tpl_path = inner_resolve(&doc_path, &tpl.base_loc)?;
+            let mut content = self.current::<PageXmlFile>(tpl_path.as_str())?;
+            if let Some(area) = &mut content.area {
+                *area = transform.area(area);
+            }
+            rotate_layers(&mut content, &transform);
+            // the copy gets objects of its own
+            for layer in content.content.iter_mut().flat_map(|c| c.layer.iter_mut()) {
+                layer.id = next_id(&mut max_id);
+                renew_ids(layer.objects.iter_mut().flatten(), &mut max_id);
+            }
+            // and lives elsewhere, so its resources are given from the root
+            for loc in content.page_res.iter_mut().flatten() {
+                *loc = PathBuf::from(format!("/{}", inner_resolve(&tpl_path, loc)?));
+            }
+            let base_loc = self.free_loc(&doc_path, "Tpls/Tpl")?;
+            self.put_xml(inner_resolve(&doc_path, &base_loc)?, content);
+            tpl_ref.template_id = next_id(&mut max_id);
+            new_templates.push(TemplatePage {
+                id: tpl_ref.template_id,
+                base_loc,
+                ..tpl.clone()
+            });
+        }
+        self.put_xml(page_path, page);
+
+        if let Some(loc) = &document.annotations {
+            let path = inner_resolve(&doc_path, loc)?;
+            let annotations = self.current::<AnnotationsXmlFile>(path.as_str())?;
+            let entries = annotations.page.iter().flatten();
+            for entry in entries.filter(|p| p.page_id == page_ref.id) {
+                let annot_path = inner_resolve(&path, &entry.file_loc)?;
+                let annot = self.xml_mut::<AnnotationXmlFile>(annot_path.as_str())?;
+                rotate_annotations(annot, rotation, &transform);
+            }
+        }
+
+        let document = self.document_mut(doc_index)?;
+        let templates = document
+            .common_data
+            .template_page
+            .get_or_insert_with(Vec::new);
+        templates.extend(new_templates);
+        document.common_data.max_unit_id = max_id;
+        Ok(())
+    }
+
+    /// the first `{prefix}_{n}/Content.xml` not in the package
+    fn free_loc(&self, doc_path: &RelativePathBuf, prefix: &str) -> Result<PathBuf> {
+        for n in 0.. {
+            let loc = PathBuf::from(format!("{prefix}_{n}/Content.xml"));
+            if !self.contains(inner_resolve(doc_path, &loc)?) {
+                return Ok(loc);
+            }
+        }
+        unreachable!()
+    }
+
+    fn signature_paths(&self, doc_index: usize) -> Result<Vec<RelativePathBuf>> {
+        let entry_path = RelativePathBuf::from(RawOfd::<S>::OFD_ENTRY);
+        let entry = self.current::<OfdXmlFile>(entry_path.as_str())?;
+        let doc_body = entry.doc_body.get(doc_index).ok_or(Error::NoSuchDocument)?;
+        let Some(loc) = &doc_body.signatures else {
+            return Ok(vec![]);
+        };
+        let path = inner_resolve(&entry_path, loc)?;
+        let signatures = self.current::<SignaturesXmlFile>(path.as_str())?;
+        let signatures = signatures.signature.iter().flatten();
+        signatures
+            .map(|s| inner_resolve(&path, &s.base_loc))
+            .collect()
+    }
+}
+
+/// take a new `ID` of the document
+fn new_id(document: &mut DocumentXmlFile) -> StId {
+    next_id(&mut document.common_data.max_unit_id)
+}
+
+fn next_id(max_id: &mut StId) -> StId {
+    *max_id += 1;
+    *max_id
+}
+
+fn renew_ids<'a>(objects: impl Iterator<Item = &'a mut VtGraphicUnit>, max_id: &mut StId) {
+    for object in objects {
+        match object {
+            VtGraphicUnit::TextObject(text) => text.id = next_id(max_id),
+            VtGraphicUnit::PathObject(path) => path.id = next_id(max_id),
+            VtGraphicUnit::ImageObject(image) => image.id = next_id(max_id),
+            VtGraphicUnit::CompositeObject(composite) => composite.id = next_id(max_id),
+            VtGraphicUnit::PageBlock(block) => {
+                block.id = next_id(max_id);
+                renew_ids(block.objects.iter_mut().flatten(), max_id);
+            }
+        }
+    }
+}
+
+fn rotate_layers(page: &mut PageXmlFile, transform: &Transform) {
+    let layers = page.content.iter_mut().flat_map(|c| c.layer.iter_mut());
+    for Layer { objects, .. } in layers {
+        rotate_objects(objects.iter_mut().flatten(), transform);
+    }
+}
+
+fn rotate_objects<'a>(objects: impl Iterator<Item = &'a mut VtGraphicUnit>, transform: &Transform) {
+    for object in objects {
+        let (boundary, ctm) = match object {
+            VtGraphicUnit::TextObject(text) => (&mut text.boundary, &mut text.ctm),
+            VtGraphicUnit::PathObject(path) => (&mut path.boundary, &mut path.ctm),
+            VtGraphicUnit::ImageObject(image) => (&mut image.boundary, &mut image.ctm),
+            VtGraphicUnit::CompositeObject(composite) => {
+                (&mut composite.boundary, &mut composite.ctm)
+            }
+            VtGraphicUnit::PageBlock(block) => {
+                rotate_objects(block.objects.iter_mut().flatten(), transform);
+                continue;
+            }
+        };
+        // objects are drawn at `Boundary` through `CTM`,
+        // the new `CTM` undoes the move of `Boundary` and turns
+        let new_boundary = transform.bbox(boundary);
+        let old = ctm
+            .as_ref()
+            .and_then(|c| <[f32; 6]>::try_from(c.as_slice()).ok())
+            .map(Transform)
+            .unwrap_or(Transform::IDENTITY);
+        let new = Transform::translate(-new_boundary.x, -new_boundary.y)
+            .concat(transform)
+            .concat(&Transform::translate(boundary.x, boundary.y))
+            .concat(&old);
+        *boundary = new_boundary;
+        *ctm = Some(new.into());
+    }
+}
+
+/// annotations are drawn in their own box, which turns as a whole
+fn rotate_annotations(annot: &mut AnnotationXmlFile, rotation: PageRotation, page: &Transform) {
+    for annot in annot.annot.iter_mut() {
+        let appearance = &mut annot.appearance;
+        let boundary = appearance.boundary;
+        if annot.no_rotate == Some(true) {
+            // keeps its looks, the top left corner goes with the page
+            let (x, y) = page.apply(boundary.x, boundary.y);
+            appearance.boundary = StBox { x, y, ..boundary };
+            continue;
+        }
+        appearance.boundary = page.bbox(&boundary);
+        let local = StBox {
+            x: 0.0,
+            y: 0.0,
+            ..boundary
+        };
+        let transform = Transform::rotate(rotation, &local);
+        rotate_objects(appearance.objects.iter_mut().flatten(), &transform);
+    }
+}
+
+/// an affine transform `[a b c d e f]`, in the order of `CTM`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Transform([f32; 6]);
+
+impl Transform {
+    const IDENTITY: Self = Self([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    fn translate(x: f32, y: f32) -> Self {
+        Self([1.0, 0.0, 0.0, 1.0, x, y])
+    }
+
+    /// turns `area` clockwise about its top left corner, then moves it back
+    /// to that corner, so a `w` by `h` box becomes a `h` by `w` one
+    fn rotate(rotation: PageRotation, area: &StBox) -> Self {
+        let (w, h) = (area.w, area.h);
+        let turn = match rotation {
+            PageRotation::Rotate90 => [0.0, 1.0, -1.0, 0.0, h, 0.0],
+            PageRotation::Rotate180 => [-1.0, 0.0, 0.0, -1.0, w, h],
+            PageRotation::Rotate270 => [0.0, -1.0, 1.0, 0.0, 0.0, w],
+        };
+        Self::translate(area.x, area.y)
+            .concat(&Self(turn))
+            .concat(&Self::translate(-area.x, -area.y))
+    }
+
+    /// `inner` first, then `self`
+    fn concat(&self, inner: &Self) -> Self {
+        let [a, b, c, d, e, f] = self.0;
+        let [ia, ib, ic, id, ie, if_] = inner.0;
+        Self([
+            a * ia + c * ib,
+            b * ia + d * ib,
+            a * ic + c * id,
+            b * ic + d * id,
+            a * ie + c * if_ + e,
+            b * ie + d * if_ + f,
+        ])
+    }
+
+    fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        let [a, b, c, d, e, f] = self.0;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+
+    /// the box holding a transformed box
+    fn bbox(&self, b: &StBox) -> StBox {
+        let [a, tb, c, d, ..] = self.0;
+        let corners = [
+            self.apply(b.x, b.y),
+            self.apply(b.x + b.w, b.y),
+            self.apply(b.x, b.y + b.h),
+            self.apply(b.x + b.w, b.y + b.h),
+        ];
+        let x = corners.iter().map(|c| c.0).fold(f32::MAX, f32::min);
+        let y = corners.iter().map(|c| c.1).fold(f32::MAX, f32::min);
+        // `+ 0.0` turns `-0` into `0`
+        StBox {
+            x: x + 0.0,
+            y: y + 0.0,
+            w: (a * b.w).abs() + (c * b.h).abs(),
+            h: (tb * b.w).abs() + (d * b.h).abs(),
+        }
+    }
+
+    fn area(&self, area: &CtPageArea) -> CtPageArea {
+        CtPageArea {
+            physical_box: self.bbox(&area.physical_box),
+            application_box: area.application_box.map(|b| self.bbox(&b)),
+            content_box: area.content_box.map(|b| self.bbox(&b)),
+            bleed_box: area.bleed_box.map(|b| self.bbox(&b)),
+        }
+    }
+}
+
+impl From<Transform> for StArray<f32> {
+    fn from(value: Transform) -> Self {
+        value.0.iter().map(|v| v + 0.0).collect::<Vec<_>>().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_bytes, from_path, Ofd};
+    use eyre::Result;
+    use ofd_base::common::{ActionType, Actions, CtAction, CtDest, Event, VtTo};
+    use ofd_base::file::document::{Bookmarks, CtBookmark, CtOutlineElem, Outlines};
+    use std::io::Cursor;
+
+    fn saved<S: Storage>(editor: &OfdEditor<S>) -> Result<Ofd<impl Storage>> {
+        let out = editor.save_to(Cursor::new(Vec::new()))?.into_inner();
+        Ok(from_bytes(out)?)
+    }
+
+    #[test]
+    fn test_transform() {
+        let area = StBox::from((0.0, 0.0, 210.0, 140.0));
+        let turn = Transform::rotate(PageRotation::Rotate90, &area);
+        assert_eq!(turn.bbox(&area), StBox::from((0.0, 0.0, 140.0, 210.0)));
+        // the top left corner goes to the top right
+        assert_eq!(turn.apply(0.0, 0.0), (140.0, 0.0));
+        let b = StBox::from((10.0, 20.0, 30.0, 5.0));
+        assert_eq!(turn.bbox(&b), StBox::from((115.0, 10.0, 5.0, 30.0)));
+
+        let back = Transform::rotate(PageRotation::Rotate270, &turn.bbox(&area));
+        assert_eq!(back.concat(&turn).bbox(&b), b);
+        let half = Transform::rotate(PageRotation::Rotate180, &area);
+        assert_eq!(half.concat(&half), Transform::IDENTITY);
+    }
+
+    #[test]
+    fn test_reorder_pages() -> Result<()> {
+        let ofd = from_path("../samples/001.ofd")?;
+        let mut editor = OfdEditor::new(ofd);
+        editor.reorder_pages(0, &[4, 3, 2, 1, 0])?;
+        let res = editor.reorder_pages(0, &[0, 0, 1, 2, 3]);
+        assert!(matches!(res, Err(Error::InvalidPageOrder)));
+        assert!(editor.reorder_pages(0, &[0, 1]).is_err());
+
+        let ofd = saved(&editor)?;
+        let doc = ofd.document_by_index(0)?;
+        let ids = doc.pages.page.iter().map(|p| p.id).collect::<Vec<_>>();
+        assert_eq!(ids, [629, 450, 271, 92, 10]);
+        assert_eq!(ofd.annotations_for_page(0, 4)?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_page() -> Result<()> {
+        let ofd = from_path("../samples/001.ofd")?;
+        let mut editor = OfdEditor::new(ofd);
+        let bookmark = |name: &str, page_id| CtBookmark {
+            name: name.into(),
+            dest: CtDest {
+                r#type: "Fit".into(),
+                page_id,
+                left: None,
+                right: None,
+                top: None,
+                bottom: None,
+                zoom: None,
+            },
+        };
+        let document = editor.document_mut(0)?;
+        document.bookmarks = Some(Bookmarks {
+            bookmarks: vec![bookmark("first", 10), bookmark("second", 92)],
+        });
+        document.outlines = Some(Outlines {
+            outline_elems: vec![CtOutlineElem {
+                title: "first".into(),
+                count: None,
+                expanded: None,
+                actions: Some(Actions {
+                    actions: vec![CtAction {
+                        event: Event::Click,
+                        region: None,
+                        action_type: ActionType::Goto {
+                            value: VtTo::Bookmark {
+                                name: "first".into(),
+                            },
+                        },
+                    }],
+                }),
+                outline_elems: None,
+            }],
+        });
+        editor.delete_page(0, 0)?;
+        assert!(matches!(editor.delete_page(0, 4), Err(Error::NoSuchPage)));
+        assert!(!editor.contains("Doc_0/Pages/Page_0/Content.xml"));
+        assert!(!editor.contains("Doc_0/Annots/Page_0/Annotation.xml"));
+
+        let ofd = saved(&editor)?;
+        assert_eq!(ofd.document_by_index(0)?.pages.page.len(), 4);
+        let annotations = ofd.xml_by_path::<AnnotationsXmlFile>("Doc_0/Annots/Annotations.xml")?;
+        assert!(annotations.page.is_none());
+        let signature = ofd.xml_by_path::<SignatureXmlFile>("Doc_0/Signs/Sign_0/Signature.xml")?;
+        assert!(signature.signed_info.stamp_annot.is_none());
+        let bookmarks = ofd.bookmarks_for_doc(0)?;
+        let names = bookmarks
+            .iter()
+            .map(|b| b.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["second"]);
+        assert_eq!(bookmarks[0].page_index, Some(0));
+        assert!(ofd.document_by_index(0)?.outlines.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_blank_page() -> Result<()> {
+        let ofd = from_path("../samples/000.ofd")?;
+        let max_id = ofd.document_by_index(0)?.common_data.max_unit_id;
+        let mut editor = OfdEditor::new(ofd);
+        let area = CtPageArea {
+            physical_box: (0.0, 0.0, 210.0, 297.0).into(),
+            application_box: None,
+            content_box: None,
+            bleed_box: None,
+        };
+        assert!(editor.insert_blank_page(0, 2, area.clone()).is_err());
+        let id = editor.insert_blank_page(0, 0, area)?;
+        assert_eq!(id, max_id + 1);
+
+        let ofd = saved(&editor)?;
+        let doc = ofd.document_by_index(0)?;
+        assert_eq!(doc.common_data.max_unit_id, id);
+        assert_eq!(doc.pages.page[0].id, id);
+        assert_eq!(
+            doc.pages.page[0].base_loc,
+            PathBuf::from("Pages/Page_1/Content.xml")
+        );
+        let page = ofd.page_by_index(0, 0)?;
+        let physical_box = page.area.as_ref().map(|a| a.physical_box);
+        assert_eq!(physical_box, Some((0.0, 0.0, 210.0, 297.0).into()));
+        assert!(page.content.content.is_none());
+        assert_eq!(ofd.validate()?, vec![]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rotate_page() -> Result<()> {
+        let ofd = from_path("../samples/000.ofd")?;
+        let max_id = ofd.document_by_index(0)?.common_data.max_unit_id;
+        let mut editor = OfdEditor::new(ofd.clone());
+        editor.rotate_page(0, 0, PageRotation::Rotate90)?;
+
+        let rotated = saved(&editor)?;
+        let page = rotated.page_by_index(0, 0)?;
+        let physical_box = page.area.as_ref().map(|a| a.physical_box);
+        assert_eq!(physical_box, Some((0.0, 0.0, 140.0, 210.0).into()));
+        let layer = &page.content.content.as_ref().unwrap().layer[0];
+        let Some(VtGraphicUnit::TextObject(text)) = layer.objects.iter().flatten().next() else {
+            panic!("not a text object");
+        };
+        // was `69 7 72 7.6749`
+        let StBox { x, y, w, h } = text.boundary;
+        assert!((x - 125.3251).abs() < 1e-4);
+        assert_eq!((y, w, h), (69.0, 7.6749, 72.0));
+        let ctm = text.ctm.as_deref().unwrap();
+        assert_eq!(ctm[..4], [0.0, 1.0, -1.0, 0.0]);
+        assert!((ctm[4] - 7.6749).abs() < 1e-4 && ctm[5] == 0.0);
+
+        // the template is a rotated copy
+        let doc = rotated.document_by_index(0)?;
+        let tpls = doc.common_data.template_page.as_ref().unwrap();
+        assert_eq!(tpls.len(), 2);
+        assert_eq!(page.template.as_ref().unwrap()[0].template_id, tpls[1].id);
+        assert!(doc.common_data.max_unit_id > max_id);
+        let path = "Doc_0/Tpls/Tpl_0/Content.xml";
+        assert_eq!(rotated.bytes(path)?, ofd.bytes(path)?);
+        assert_eq!(rotated.validate()?, vec![]);
+        Ok(())
+    }
+}
@@ -0,0 +1,375 @@
+use ofd_base::common::{ActionType, Actions, CtColor, CtDest, VtTo};
+use ofd_base::file::annotation::{AnnotationXmlFile, AnnotationsXmlFile};
+use ofd_base::file::document::{CtOutlineElem, DocumentXmlFile};
+use ofd_base::file::page::{Layer, PageXmlFile, VtGraphicUnit};
+use ofd_base::file::res::{Resource, ResourceXmlFile};
+use ofd_base::{StId, StRefId};
+use std::collections::HashMap;
+
+/// sees every `ID` and every reference to one
+pub(crate) trait IdVisitor {
+    /// an `ID` or a reference to one
+    fn id(&mut self, id: &mut StId);
+
+    /// the `ColorSpace` of a color, `None` stands for the default color space
+    fn color_space(&mut self, color_space: &mut Option<StRefId>) {
+        if let Some(id) = color_space {
+            self.id(id);
+        }
+    }
+
+    /// the name of a bookmark, or a reference to one
+    fn bookmark(&mut self, _name: &mut String) {}
+}
+
+/// xml holding `ID`s or references to them
+pub(crate) trait VisitIds {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V);
+}
+
+impl<T: VisitIds> VisitIds for Option<T> {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        if let Some(t) = self {
+            t.visit_ids(v);
+        }
+    }
+}
+
+impl<T: VisitIds> VisitIds for Vec<T> {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        self.iter_mut().for_each(|t| t.visit_ids(v));
+    }
+}
+
+impl<T: VisitIds> VisitIds for Box<T> {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        self.as_mut().visit_ids(v);
+    }
+}
+
+impl VisitIds for StId {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        v.id(self);
+    }
+}
+
+impl VisitIds for DocumentXmlFile {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        let common_data = &mut self.common_data;
+        for tpl in common_data.template_page.iter_mut().flatten() {
+            v.id(&mut tpl.id);
+        }
+        common_data.default_cs.visit_ids(v);
+        for page in self.pages.page.iter_mut() {
+            v.id(&mut page.id);
+        }
+        if let Some(outlines) = &mut self.outlines {
+            outlines.outline_elems.visit_ids(v);
+        }
+        self.actions.visit_ids(v);
+        for bookmark in self
+            .bookmarks
+            .iter_mut()
+            .flat_map(|b| b.bookmarks.iter_mut())
+        {
+            v.bookmark(&mut bookmark.name);
+            bookmark.dest.visit_ids(v);
+        }
+    }
+}
+
+impl VisitIds for CtOutlineElem {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        self.actions.visit_ids(v);
+        self.outline_elems.visit_ids(v);
+    }
+}
+
+impl VisitIds for Actions {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        for action in self.actions.iter_mut() {
+            match &mut action.action_type {
+                ActionType::Goto {
+                    value: VtTo::Dest(dest),
+                } => dest.visit_ids(v),
+                ActionType::Goto {
+                    value: VtTo::Bookmark { name },
+                } => v.bookmark(name),
+                ActionType::Sound { resource_id, .. } | ActionType::Movie { resource_id, .. } => {
+                    v.id(resource_id)
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl VisitIds for CtDest {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        v.id(&mut self.page_id);
+    }
+}
+
+impl VisitIds for PageXmlFile {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        for tpl in self.template.iter_mut().flatten() {
+            v.id(&mut tpl.template_id);
+        }
+        if let Some(content) = &mut self.content {
+            content.layer.visit_ids(v);
+        }
+    }
+}
+
+impl VisitIds for Layer {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        v.id(&mut self.id);
+        self.draw_param.visit_ids(v);
+        self.objects.visit_ids(v);
+    }
+}
+
+impl VisitIds for VtGraphicUnit {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        match self {
+            VtGraphicUnit::TextObject(text) => {
+                v.id(&mut text.id);
+                v.id(&mut text.font);
+                text.draw_param.visit_ids(v);
+                text.fill_color.visit_ids(v);
+                text.stroke_color.visit_ids(v);
+                text.actions.visit_ids(v);
+            }
+            VtGraphicUnit::PathObject(path) => {
+                v.id(&mut path.id);
+                path.draw_param.visit_ids(v);
+                path.fill_color.visit_ids(v);
+                path.stroke_color.visit_ids(v);
+                path.actions.visit_ids(v);
+            }
+            VtGraphicUnit::ImageObject(image) => {
+                v.id(&mut image.id);
+                v.id(&mut image.resource_id);
+                image.substitution.visit_ids(v);
+                image.image_mask.visit_ids(v);
+                image.draw_param.visit_ids(v);
+                if let Some(border) = &mut image.border {
+                    border.border_color.visit_ids(v);
+                }
+                image.actions.visit_ids(v);
+            }
+            VtGraphicUnit::CompositeObject(composite) => {
+                v.id(&mut composite.id);
+                v.id(&mut composite.resource_id);
+                composite.draw_param.visit_ids(v);
+                composite.actions.visit_ids(v);
+            }
+            VtGraphicUnit::PageBlock(block) => {
+                v.id(&mut block.id);
+                block.objects.visit_ids(v);
+            }
+        }
+    }
+}
+
+impl VisitIds for CtColor {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        v.color_space(&mut self.color_space);
+        if let Some(pattern) = &mut self.pattern {
+            for cell in pattern.cell_content.iter_mut() {
+                cell.thumbnail.visit_ids(v);
+                cell.base.visit_ids(v);
+            }
+        }
+        let segments = self.axial_shd.iter_mut().flat_map(|s| s.segment.iter_mut());
+        let segments = segments.chain(
+            self.radial_shd
+                .iter_mut()
+                .flat_map(|s| s.segment.iter_mut()),
+        );
+        for segment in segments {
+            segment.color.visit_ids(v);
+        }
+        if let Some(shd) = &mut self.gouraud_shd {
+            shd.back_color.visit_ids(v);
+        }
+        if let Some(shd) = &mut self.la_gouraud_shd {
+            shd.back_color.visit_ids(v);
+        }
+    }
+}
+
+impl VisitIds for ResourceXmlFile {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        self.resources.visit_ids(v);
+    }
+}
+
+impl VisitIds for Resource {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        match self {
+            Resource::ColorSpaces(r) => r.color_spaces.iter_mut().for_each(|cs| v.id(&mut cs.id)),
+            Resource::DrawParams(r) => {
+                for draw_param in r.draw_params.iter_mut() {
+                    v.id(&mut draw_param.id);
+                    draw_param.relative.visit_ids(v);
+                    draw_param.fill_color.visit_ids(v);
+                    draw_param.stroke_color.visit_ids(v);
+                }
+            }
+            Resource::Fonts(r) => r.fonts.iter_mut().for_each(|f| v.id(&mut f.id)),
+            Resource::MultiMedias(r) => r.multi_medias.iter_mut().for_each(|m| v.id(&mut m.id)),
+            Resource::CompositeGraphicUnits(r) => {
+                for unit in r.composite_graphic_units.iter_mut() {
+                    v.id(&mut unit.id);
+                    unit.base.thumbnail.visit_ids(v);
+                    unit.base.substitution.visit_ids(v);
+                    unit.base.content.visit_ids(v);
+                }
+            }
+        }
+    }
+}
+
+impl VisitIds for AnnotationsXmlFile {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        for page in self.page.iter_mut().flatten() {
+            v.id(&mut page.page_id);
+        }
+    }
+}
+
+impl VisitIds for AnnotationXmlFile {
+    fn visit_ids<V: IdVisitor + ?Sized>(&mut self, v: &mut V) {
+        for annot in self.annot.iter_mut() {
+            v.id(&mut annot.id);
+            annot.appearance.objects.visit_ids(v);
+        }
+    }
+}
+
+/// gives the `ID`s of a document new numbers, starting from a given one
+///
+/// an `ID` keeps its new number wherever it shows up,
+/// so references follow the elements they point to.
+#[derive(Debug)]
+pub(crate) struct Renumber {
+    ids: HashMap<StId, StId>,
+    next: StId,
+    /// put into colors without a color space
+    default_cs: Option<StRefId>,
+    bookmarks: HashMap<String, String>,
+}
+
+impl Renumber {
+    /// `next` is the first new `ID`
+    pub(crate) fn new(next: StId) -> Self {
+        Self {
+            ids: HashMap::new(),
+            next,
+            default_cs: None,
+            bookmarks: HashMap::new(),
+        }
+    }
+
+    /// the `ID` following the ones given out
+    pub(crate) fn next(&self) -> StId {
+        self.next
+    }
+
+    /// the new number of an `ID`, given on first sight
+    pub(crate) fn get(&mut self, id: StId) -> StId {
+        *self.ids.entry(id).or_insert_with(|| {
+            self.next += 1;
+            self.next - 1
+        })
+    }
+
+    /// give `id` a number given out already, to merge two elements into one
+    pub(crate) fn alias(&mut self, id: StId, new_id: StId) {
+        self.ids.insert(id, new_id);
+    }
+
+    /// give a bookmark a new name, for the names taken already
+    pub(crate) fn rename_bookmark(&mut self, name: String, new_name: String) {
+        self.bookmarks.insert(name, new_name);
+    }
+
+    /// colors relying on the default color space of the source document
+    /// get it set explicitly, as the default is gone after a merge
+    pub(crate) fn set_default_cs(&mut self, default_cs: Option<StRefId>) {
+        self.default_cs = default_cs.map(|id| self.get(id));
+    }
+}
+
+impl IdVisitor for Renumber {
+    fn id(&mut self, id: &mut StId) {
+        *id = self.get(*id);
+    }
+
+    fn color_space(&mut self, color_space: &mut Option<StRefId>) {
+        match color_space {
+            Some(id) => self.id(id),
+            None => *color_space = self.default_cs,
+        }
+    }
+
+    fn bookmark(&mut self, name: &mut String) {
+        if let Some(new_name) = self.bookmarks.get(name) {
+            *name = new_name.clone();
+        }
+    }
+}
+
+/// collects ids, without changing them
+impl IdVisitor for Vec<StId> {
+    fn id(&mut self, id: &mut StId) {
+        self.push(*id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_path;
+    use eyre::Result;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_renumber() -> Result<()> {
+        let ofd = from_path("../samples/000.ofd")?;
+        let mut page = Rc::unwrap_or_clone(ofd.page_by_index(0, 0)?.content);
+        let mut ids = vec![];
+        page.clone().visit_ids(&mut ids);
+        assert!(!ids.is_empty());
+
+        let mut renumber = Renumber::new(1000);
+        renumber.alias(ids[0], 1);
+        page.visit_ids(&mut renumber);
+        let mut new_ids = vec![];
+        page.visit_ids(&mut new_ids);
+        assert_eq!(new_ids.len(), ids.len());
+        assert_eq!(new_ids[0], 1);
+        assert!(new_ids[1..].iter().all(|id| *id >= 1000));
+        // the same id gets the same number
+        for (i, id) in ids.iter().enumerate() {
+            let j = ids.iter().position(|other| other == id).unwrap();
+            assert_eq!(new_ids[i], new_ids[j]);
+        }
+        assert!(renumber.next() > 1000);
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_cs() {
+        let mut renumber = Renumber::new(10);
+        renumber.set_default_cs(Some(3));
+        let ele: minidom::Element =
+            format!(r#"<FillColor xmlns="{}" Value="0 0 0"/>"#, crate::OFD_NS)
+                .parse()
+                .unwrap();
+        let mut color: CtColor = xdom::de::from_ele(&ele).unwrap();
+        color.visit_ids(&mut renumber);
+        assert_eq!(color.color_space, Some(10));
+    }
+}
@@ -0,0 +1,272 @@
+use crate::container::Ofd;
+use crate::editor::OfdEditor;
+use crate::error::Result;
+use crate::storage::Storage;
+use crate::OFD_NS;
+use minidom::Element;
+use ofd_base::file::annotation::{self, Annot, AnnotationXmlFile, AnnotationsXmlFile};
+use ofd_base::file::document::{CtPageArea, DocumentXmlFile, Page, TemplatePage};
+use ofd_base::file::page::{Content, Layer, PageXmlFile};
+use ofd_base::file::res::ResourceXmlFile;
+use relative_path::RelativePathBuf;
+use serde::de::DeserializeOwned;
+use std::any::TypeId;
+use std::fs::File;
+use std::io::{BufWriter, Seek, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use xdom::de::{from_ele, XmlDeError};
+
+/// A4, for a document without a readable `PageArea`
+const DEFAULT_PHYSICAL_BOX: &str = "0 0 210 297";
+
+/// something fixed up while reading in lenient mode,
+/// see [Ofd::set_lenient]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recovery {
+    /// the item recovered
+    pub path: RelativePathBuf,
+    pub kind: RecoveryKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum RecoveryKind {
+    #[error("DocRoot of document {doc_index} not found, located by scanning the package")]
+    DocRootLocated { doc_index: usize },
+
+    #[error("missing item, read as empty")]
+    MissingItem,
+
+    #[error("invalid PageArea, replaced by an A4 page")]
+    DefaultPageArea,
+
+    #[error(r#"skipped unparsable element "{element}": {error}"#)]
+    SkippedElement { element: String, error: String },
+}
+
+impl<S: Storage> Ofd<S> {
+    /// read all the documents leniently, including pages,
+    /// templates, resources and annotations
+    ///
+    /// returns all the recoveries so far.
+    /// the reading mode is restored afterwards.
+    pub fn repair(&self) -> Result<Vec<Recovery>> {
+        let lenient = self.is_lenient();
+        self.set_lenient(true);
+        let res = self.read_all();
+        self.set_lenient(lenient);
+        res?;
+        Ok(self.recoveries())
+    }
+
+    fn read_all(&self) -> Result<()> {
+        let entry = self.entry()?;
+        for doc_index in 0..entry.doc_body.len() {
+            let doc = self.document_by_index(doc_index)?;
+            for page_index in 0..doc.pages.page.len() {
+                self.templates_for_page(doc_index, page_index)?;
+                self.resources_for_page(doc_index, page_index)?;
+                self.annotations_for_page(doc_index, page_index)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// [Ofd::repair] and write the package with the repaired items into `writer`
+    pub fn save_repaired_to<W: Write + Seek>(&self, writer: W) -> Result<W> {
+        self.repair()?;
+        let mut editor = OfdEditor::new(self.clone());
+        for (path, bytes) in self.repaired_items() {
+            editor.put_bytes(path, bytes);
+        }
+        let located = self.located_roots();
+        if !located.is_empty() {
+            let entry = editor.entry_mut()?;
+            for (doc_index, path) in located {
+                entry.doc_body[doc_index].doc_root = Some(PathBuf::from(path.as_str()));
+            }
+        }
+        editor.save_to(writer)
+    }
+
+    /// [Ofd::repair] and write the package with the repaired items to a file
+    pub fn save_repaired(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = File::create(path)?;
+        let mut w = self.save_repaired_to(BufWriter::new(file))?;
+        w.flush()?;
+        Ok(())
+    }
+}
+
+/// an empty root element standing for a missing item,
+/// `None` for the items which can not be empty
+pub(crate) fn empty_root<R: 'static>() -> Option<Element> {
+    let tid = TypeId::of::<R>();
+    if tid == TypeId::of::<PageXmlFile>() {
+        Some(Element::bare("Page", OFD_NS))
+    } else if tid == TypeId::of::<ResourceXmlFile>() {
+        Some(Element::builder("Res", OFD_NS).attr("BaseLoc", "").build())
+    } else if tid == TypeId::of::<AnnotationsXmlFile>() {
+        Some(Element::bare("Annotations", OFD_NS))
+    } else {
+        None
+    }
+}
+
+/// fix or drop the elements of `root` so it may parse as `R`
+pub(crate) fn repair<R: 'static>(root: &mut Element) -> Vec<RecoveryKind> {
+    let mut kinds = vec![];
+    let tid = TypeId::of::<R>();
+    if tid == TypeId::of::<DocumentXmlFile>() {
+        repair_document(root, &mut kinds);
+    } else if tid == TypeId::of::<PageXmlFile>() {
+        repair_page(root, &mut kinds);
+    } else if tid == TypeId::of::<ResourceXmlFile>() {
+        repair_res(root, &mut kinds);
+    } else if tid == TypeId::of::<AnnotationsXmlFile>() {
+        retain_children(root, &mut kinds, parse::<annotation::Page>);
+    } else if tid == TypeId::of::<AnnotationXmlFile>() {
+        retain_children(root, &mut kinds, parse::<Annot>);
+    }
+    kinds
+}
+
+fn repair_document(root: &mut Element, kinds: &mut Vec<RecoveryKind>) {
+    let ns = root.ns();
+    if let Some(common_data) = root.get_child_mut("CommonData", ns.as_str()) {
+        let area = common_data.get_child("PageArea", ns.as_str());
+        if area.is_none_or(|a| parse::<CtPageArea>(a).is_err()) {
+            set_default_page_area(common_data);
+            kinds.push(RecoveryKind::DefaultPageArea);
+        }
+        retain_children(common_data, kinds, |c| match c.name() {
+            "TemplatePage" => parse::<TemplatePage>(c),
+            _ => Ok(()),
+        });
+    }
+    if let Some(pages) = root.get_child_mut("Pages", ns.as_str()) {
+        retain_children(pages, kinds, parse::<Page>);
+    }
+
+    // the optional elements are checked one by one with the required ones
+    let mut required = shell(root);
+    for name in ["CommonData", "Pages"] {
+        if let Some(c) = root.get_child(name, ns.as_str()) {
+            required.append_child(c.clone());
+        }
+    }
+    retain_children(root, kinds, |c| match c.name() {
+        "CommonData" | "Pages" => Ok(()),
+        _ => probe::<DocumentXmlFile>(&required, c),
+    });
+}
+
+/// replace `PageArea`, or put one right after `MaxUnitID`
+fn set_default_page_area(common_data: &mut Element) {
+    let ns = common_data.ns();
+    let physical_box = Element::builder("PhysicalBox", ns.as_str())
+        .append(DEFAULT_PHYSICAL_BOX)
+        .build();
+    let mut area = Some(
+        Element::builder("PageArea", ns.as_str())
+            .append(physical_box)
+            .build(),
+    );
+    for node in common_data.take_nodes() {
+        let name = node.as_element().map(|e| e.name().to_string());
+        if name.as_deref() == Some("PageArea") {
+            continue;
+        }
+        common_data.append_node(node);
+        if name.as_deref() == Some("MaxUnitID") {
+            if let Some(area) = area.take() {
+                common_data.append_child(area);
+            }
+        }
+    }
+    if let Some(area) = area {
+        common_data.append_child(area);
+    }
+}
+
+fn repair_page(root: &mut Element, kinds: &mut Vec<RecoveryKind>) {
+    let ns = root.ns();
+    if let Some(content) = root.get_child_mut("Content", ns.as_str()) {
+        let layer = Element::builder("Layer", ns.as_str())
+            .attr("ID", "0")
+            .build();
+        for l in content.children_mut() {
+            repair_objects(l, &layer, kinds);
+        }
+        let content_shell = shell(content);
+        retain_children(content, kinds, |c| probe::<Content>(&content_shell, c));
+    }
+    let page = shell(root);
+    retain_children(root, kinds, |c| match c.name() {
+        "Content" => Ok(()),
+        _ => probe::<PageXmlFile>(&page, c),
+    });
+}
+
+/// drop the objects failing to parse in a layer, page blocks are repaired recursively
+fn repair_objects(parent: &mut Element, layer: &Element, kinds: &mut Vec<RecoveryKind>) {
+    for block in parent.children_mut().filter(|c| c.name() == "PageBlock") {
+        repair_objects(block, layer, kinds);
+    }
+    retain_children(parent, kinds, |c| probe::<Layer>(layer, c));
+}
+
+fn repair_res(root: &mut Element, kinds: &mut Vec<RecoveryKind>) {
+    let res = shell(root);
+    for group in root.children_mut() {
+        let group_shell = shell(group);
+        retain_children(group, kinds, |c| {
+            let mut group = group_shell.clone();
+            group.append_child(c.clone());
+            probe::<ResourceXmlFile>(&res, &group)
+        });
+    }
+    retain_children(root, kinds, |c| probe::<ResourceXmlFile>(&res, c));
+}
+
+/// an element with the name and attributes of `element` only
+fn shell(element: &Element) -> Element {
+    let mut shell = Element::bare(element.name(), element.ns());
+    for (name, value) in element.attrs() {
+        shell.set_attr(name, value);
+    }
+    shell
+}
+
+/// parse `child` in a copy of `parent`
+fn probe<T: DeserializeOwned>(
+    parent: &Element,
+    child: &Element,
+) -> std::result::Result<(), XmlDeError> {
+    let mut parent = parent.clone();
+    parent.append_child(child.clone());
+    parse::<T>(&parent)
+}
+
+fn parse<T: DeserializeOwned>(element: &Element) -> std::result::Result<(), XmlDeError> {
+    from_ele::<T>(element).map(drop)
+}
+
+/// drop the child elements rejected by `check`
+fn retain_children<F>(parent: &mut Element, kinds: &mut Vec<RecoveryKind>, check: F)
+where
+    F: Fn(&Element) -> std::result::Result<(), XmlDeError>,
+{
+    for node in parent.take_nodes() {
+        if let Some(element) = node.as_element() {
+            if let Err(e) = check(element) {
+                kinds.push(RecoveryKind::SkippedElement {
+                    element: element.name().into(),
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        }
+        parent.append_node(node);
+    }
+}
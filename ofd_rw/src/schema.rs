@@ -0,0 +1,717 @@
+//! validation against the xml schemas of the spec, see `xsd/` in the repository
+//!
+//! only the subset of xsd used by the spec is supported.
+
+use crate::container::{read_element, Ofd};
+use crate::error::Result;
+use crate::storage::Storage;
+use crate::OFD_NS;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use minidom::Element;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::OnceLock;
+
+const XS_NS: &str = "http://www.w3.org/2001/XMLSchema";
+
+const SCHEMAS: [&str; 13] = [
+    include_str!("../../xsd/Definitions.xsd"),
+    include_str!("../../xsd/Annotation.xsd"),
+    include_str!("../../xsd/Annotations.xsd"),
+    include_str!("../../xsd/Attachments.xsd"),
+    include_str!("../../xsd/CustomTags.xsd"),
+    include_str!("../../xsd/Document.xsd"),
+    include_str!("../../xsd/Extensions.xsd"),
+    include_str!("../../xsd/OFD.xsd"),
+    include_str!("../../xsd/Page.xsd"),
+    include_str!("../../xsd/Res.xsd"),
+    include_str!("../../xsd/Signature.xsd"),
+    include_str!("../../xsd/Signatures.xsd"),
+    include_str!("../../xsd/Version.xsd"),
+];
+
+/// a schema violation found by [Ofd::validate_schema]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// the item in the package
+    pub path: String,
+    /// path of the element in the item, like `/Page/Content/Layer[1]/TextObject[3]`
+    pub element: String,
+    pub message: String,
+}
+
+impl<S: Storage> Ofd<S> {
+    /// check each xml item of the package against the schemas of the spec
+    ///
+    /// items are matched to the schemas by their root element,
+    /// those outside the ofd namespace, such as custom tag files, are skipped.
+    pub fn validate_schema(&self) -> Result<Vec<SchemaViolation>> {
+        let schema = Schema::ofd();
+        let mut violations = vec![];
+        for name in self.item_names() {
+            if !name.to_ascii_lowercase().ends_with(".xml") {
+                continue;
+            }
+            let bytes = self.bytes(name.as_str())?;
+            let root = match read_element(bytes.as_slice()) {
+                Ok(root) => root,
+                Err(e) => {
+                    violations.push(SchemaViolation {
+                        path: name,
+                        element: "/".into(),
+                        message: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+            if root.ns() != OFD_NS || !schema.elements.contains_key(root.name()) {
+                continue;
+            }
+            violations.extend(
+                schema
+                    .validate(&root)
+                    .into_iter()
+                    .map(|(element, message)| SchemaViolation {
+                        path: name.clone(),
+                        element,
+                        message,
+                    }),
+            );
+        }
+        Ok(violations)
+    }
+}
+
+/// the global definitions of a set of schemas
+#[derive(Debug, Default)]
+pub struct Schema {
+    elements: HashMap<String, ElementDecl>,
+    complex_types: HashMap<String, ComplexType>,
+    simple_types: HashMap<String, SimpleType>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Occurs {
+    min: usize,
+    /// `None` for unbounded
+    max: Option<usize>,
+}
+
+#[derive(Debug)]
+enum Particle {
+    Element(ElementDecl),
+    Sequence(Vec<Particle>, Occurs),
+    Choice(Vec<Particle>, Occurs),
+}
+
+#[derive(Debug)]
+struct ElementDecl {
+    name: String,
+    occurs: Occurs,
+    ty: TypeRef,
+}
+
+#[derive(Debug)]
+enum TypeRef {
+    Named(String),
+    Complex(Box<ComplexType>),
+    Simple(SimpleType),
+    /// `xs:anyType` and elements without a type, content not checked
+    Any,
+}
+
+#[derive(Debug, Default)]
+struct ComplexType {
+    /// base of `complexContent/extension`
+    base: Option<String>,
+    content: Option<Particle>,
+    attributes: Vec<AttributeDecl>,
+    /// type of `simpleContent`
+    text: Option<SimpleType>,
+}
+
+#[derive(Debug)]
+struct AttributeDecl {
+    name: String,
+    ty: SimpleType,
+    required: bool,
+    fixed: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum SimpleType {
+    /// a built in type like `xs:int`, or a named simple type
+    Named(String),
+    Enumeration(Vec<String>),
+}
+
+impl Schema {
+    /// the schemas of the spec
+    pub fn ofd() -> &'static Schema {
+        static SCHEMA: OnceLock<Schema> = OnceLock::new();
+        SCHEMA.get_or_init(|| {
+            let mut schema = Schema::default();
+            for xsd in SCHEMAS {
+                let root = strip_comments(xsd)
+                    .parse::<Element>()
+                    .expect("bundled xsd is valid xml");
+                schema.add(&root);
+            }
+            schema
+        })
+    }
+
+    /// add the global definitions in an `xs:schema` element
+    fn add(&mut self, schema: &Element) {
+        for child in schema.children().filter(|c| c.ns() == XS_NS) {
+            let Some(name) = child.attr("name") else {
+                continue;
+            };
+            match child.name() {
+                "element" => {
+                    self.elements.insert(name.into(), element_decl(child));
+                }
+                "complexType" => {
+                    self.complex_types.insert(name.into(), complex_type(child));
+                }
+                "simpleType" => {
+                    self.simple_types.insert(name.into(), simple_type(child));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// validate a root element, returns element paths and messages
+    pub fn validate(&self, root: &Element) -> Vec<(String, String)> {
+        let mut violations = vec![];
+        let path = format!("/{}", root.name());
+        match self.elements.get(root.name()) {
+            Some(decl) => self.validate_element(root, &decl.ty, &path, &mut violations),
+            None => violations.push((path, "unknown root element".into())),
+        }
+        violations
+    }
+
+    fn validate_element(
+        &self,
+        element: &Element,
+        ty: &TypeRef,
+        path: &str,
+        violations: &mut Vec<(String, String)>,
+    ) {
+        let mut report = |message: String| violations.push((path.to_string(), message));
+        let complex = match ty {
+            TypeRef::Any => return,
+            TypeRef::Simple(st) => {
+                self.check_text(element, st, &mut report);
+                return;
+            }
+            TypeRef::Complex(ct) => ct.as_ref(),
+            TypeRef::Named(name) => match self.complex_types.get(name) {
+                Some(ct) => ct,
+                None => {
+                    self.check_text(element, &SimpleType::Named(name.clone()), &mut report);
+                    return;
+                }
+            },
+        };
+
+        // the chain of extended types, from the base
+        let mut chain = vec![complex];
+        while let Some(base) = chain.last().and_then(|c| c.base.as_ref()) {
+            match self.complex_types.get(base) {
+                Some(ct) => chain.push(ct),
+                None => break,
+            }
+        }
+        chain.reverse();
+
+        let attributes = chain.iter().flat_map(|c| c.attributes.iter());
+        self.check_attributes(element, attributes, &mut report);
+        if let Some(text) = chain.iter().find_map(|c| c.text.as_ref()) {
+            self.check_text(element, text, &mut report);
+        }
+
+        let particles = chain
+            .iter()
+            .filter_map(|c| c.content.as_ref())
+            .collect::<Vec<_>>();
+        let children = element.children().collect::<Vec<_>>();
+        for child in children.iter().filter(|c| c.ns() != OFD_NS) {
+            report(format!(
+                r#"element "{}" not in ofd namespace"#,
+                child.name()
+            ));
+        }
+        let names = children.iter().map(|c| c.name()).collect::<Vec<_>>();
+        let mut furthest = 0;
+        let mut ends = BTreeSet::from([0]);
+        for p in particles.iter() {
+            ends = ends
+                .into_iter()
+                .flat_map(|s| match_particle(p, &names, s, &mut furthest))
+                .collect();
+        }
+        if !ends.contains(&names.len()) {
+            if furthest < names.len() {
+                report(format!(r#"unexpected element "{}""#, names[furthest]));
+            } else {
+                report("missing required element".into());
+            }
+        }
+
+        let mut decls = HashMap::new();
+        for p in particles {
+            collect_decls(p, &mut decls);
+        }
+        let mut counts = HashMap::<&str, usize>::new();
+        for child in children {
+            let count = counts.entry(child.name()).or_default();
+            *count += 1;
+            if let Some(decl) = decls.get(child.name()) {
+                let path = format!("{}/{}[{}]", path, child.name(), count);
+                self.validate_element(child, &decl.ty, &path, violations);
+            }
+        }
+    }
+
+    fn check_attributes<'a>(
+        &self,
+        element: &Element,
+        decls: impl Iterator<Item = &'a AttributeDecl>,
+        report: &mut impl FnMut(String),
+    ) {
+        let decls = decls.collect::<Vec<_>>();
+        for decl in decls.iter() {
+            match element.attr(&decl.name) {
+                Some(value) => {
+                    if let Some(fixed) = decl.fixed.as_ref().filter(|f| *f != value) {
+                        report(format!(
+                            r#"attribute "{}" must be "{}", found "{}""#,
+                            decl.name, fixed, value
+                        ));
+                    }
+                    if let Err(e) = self.check_value(&decl.ty, value) {
+                        report(format!(r#"attribute "{}": {}"#, decl.name, e));
+                    } else if decl.name == "CTM" && array_len(value) != Some(6) {
+                        report(format!(r#"attribute "CTM": "{}" has not 6 numbers"#, value));
+                    }
+                }
+                None if decl.required => {
+                    report(format!(r#"missing required attribute "{}""#, decl.name))
+                }
+                None => {}
+            }
+        }
+        for (name, _) in element.attrs() {
+            if !name.contains(':') && !decls.iter().any(|d| d.name == name) {
+                report(format!(r#"unexpected attribute "{}""#, name));
+            }
+        }
+    }
+
+    fn check_text(&self, element: &Element, ty: &SimpleType, report: &mut impl FnMut(String)) {
+        if element.children().next().is_some() {
+            report("unexpected child element in simple content".into());
+        }
+        if let Err(e) = self.check_value(ty, element.text().trim()) {
+            report(e);
+        }
+    }
+
+    /// check a value against a simple type
+    fn check_value(&self, ty: &SimpleType, value: &str) -> std::result::Result<(), String> {
+        let name = match ty {
+            SimpleType::Enumeration(values) => {
+                return if values.iter().any(|v| v == value) {
+                    Ok(())
+                } else {
+                    Err(format!(r#""{}" is not one of {:?}"#, value, values))
+                };
+            }
+            SimpleType::Named(name) => name.as_str(),
+        };
+        let numbers = |n: usize| {
+            let parts = value.split_whitespace().collect::<Vec<_>>();
+            parts.len() == n && parts.iter().all(|p| p.parse::<f64>().is_ok())
+        };
+        // white spaces are collapsed for all the built in types but `xs:string`
+        let trimmed = value.trim();
+        let valid = match name {
+            // the types with patterns defined in the spec text but not in the xsd
+            "ST_Box" => numbers(4),
+            "ST_Pos" => numbers(2),
+            "ST_Array" => array_len(value).is_some(),
+            "xs:boolean" => matches!(trimmed, "true" | "false" | "1" | "0"),
+            "xs:int" => trimmed.parse::<i32>().is_ok(),
+            "xs:unsignedInt" => trimmed.parse::<u32>().is_ok(),
+            "xs:double" => trimmed.parse::<f64>().is_ok(),
+            "xs:date" => is_date(trimmed),
+            "xs:dateTime" => is_date_time(trimmed),
+            "xs:base64Binary" => value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+/= \t\r\n".contains(c)),
+            "xs:ID" | "xs:IDREF" => !trimmed.is_empty(),
+            n if n.starts_with("xs:") => true,
+            n => {
+                return match self.simple_types.get(n) {
+                    Some(st) => self.check_value(st, value),
+                    None => Ok(()),
+                }
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(format!(r#""{}" is not a valid {}"#, value, name))
+        }
+    }
+}
+
+/// minidom does not accept comments
+fn strip_comments(xml: &str) -> String {
+    let mut res = String::with_capacity(xml.len());
+    let mut rest = xml;
+    while let Some(start) = rest.find("<!--") {
+        res.push_str(&rest[..start]);
+        rest = rest[start..]
+            .find("-->")
+            .map_or("", |end| &rest[start + end + 3..]);
+    }
+    res.push_str(rest);
+    res
+}
+
+fn is_date(value: &str) -> bool {
+    // a time zone is allowed after the date
+    let Some(date) = value.get(..value.len().min(10)) else {
+        return false;
+    };
+    let zone = &value[date.len()..];
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok()
+        && (zone.is_empty()
+            || zone == "Z"
+            || DateTime::parse_from_str(
+                &format!("2000-01-01T00:00:00{}", zone),
+                "%Y-%m-%dT%H:%M:%S%:z",
+            )
+            .is_ok())
+}
+
+fn is_date_time(value: &str) -> bool {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").is_ok()
+        || DateTime::parse_from_rfc3339(value).is_ok()
+}
+
+/// possible end positions after matching `particle` on `names` from `start`
+fn match_particle(
+    particle: &Particle,
+    names: &[&str],
+    start: usize,
+    furthest: &mut usize,
+) -> BTreeSet<usize> {
+    match particle {
+        Particle::Element(decl) => {
+            let count = names[start..]
+                .iter()
+                .take_while(|n| **n == decl.name)
+                .count();
+            *furthest = (*furthest).max(start + count);
+            let max = decl.occurs.max.map_or(count, |m| m.min(count));
+            (start + decl.occurs.min..=start + max).collect()
+        }
+        Particle::Sequence(items, occurs) => repeat(*occurs, start, |s| {
+            items.iter().fold(BTreeSet::from([s]), |ends, item| {
+                ends.into_iter()
+                    .flat_map(|s| match_particle(item, names, s, furthest))
+                    .collect()
+            })
+        }),
+        Particle::Choice(items, occurs) => repeat(*occurs, start, |s| {
+            items
+                .iter()
+                .flat_map(|item| match_particle(item, names, s, furthest))
+                .collect()
+        }),
+    }
+}
+
+/// match a group `occurs` times
+fn repeat(
+    occurs: Occurs,
+    start: usize,
+    mut once: impl FnMut(usize) -> BTreeSet<usize>,
+) -> BTreeSet<usize> {
+    let mut ends = BTreeSet::new();
+    if occurs.min == 0 {
+        ends.insert(start);
+    }
+    let mut current = BTreeSet::from([start]);
+    let mut seen = BTreeSet::new();
+    let mut times = 0;
+    while !current.is_empty() && occurs.max.is_none_or(|m| times < m) {
+        times += 1;
+        let next = current
+            .iter()
+            .flat_map(|s| once(*s))
+            .collect::<BTreeSet<_>>();
+        if times >= occurs.min {
+            ends.extend(next.iter().copied());
+        }
+        // positions already tried can not lead further
+        current = if times >= occurs.min {
+            next.into_iter().filter(|s| seen.insert(*s)).collect()
+        } else {
+            next
+        };
+    }
+    ends
+}
+
+fn collect_decls<'a>(particle: &'a Particle, decls: &mut HashMap<&'a str, &'a ElementDecl>) {
+    match particle {
+        Particle::Element(decl) => {
+            decls.entry(decl.name.as_str()).or_insert(decl);
+        }
+        Particle::Sequence(items, _) | Particle::Choice(items, _) => {
+            items.iter().for_each(|i| collect_decls(i, decls));
+        }
+    }
+}
+
+fn xs_children(element: &Element) -> impl Iterator<Item = &Element> {
+    element.children().filter(|c| c.ns() == XS_NS)
+}
+
+fn occurs(element: &Element) -> Occurs {
+    let min = element
+        .attr("minOccurs")
+        .and_then(|m| m.parse().ok())
+        .unwrap_or(1);
+    let max = match element.attr("maxOccurs") {
+        Some("unbounded") => None,
+        Some(m) => m.parse().ok(),
+        None => Some(1),
+    };
+    Occurs { min, max }
+}
+
+fn element_decl(element: &Element) -> ElementDecl {
+    let ty = if let Some(ty) = element.attr("type") {
+        if ty == "xs:anyType" {
+            TypeRef::Any
+        } else {
+            TypeRef::Named(ty.into())
+        }
+    } else if let Some(ct) = element.get_child("complexType", XS_NS) {
+        TypeRef::Complex(Box::new(complex_type(ct)))
+    } else if let Some(st) = element.get_child("simpleType", XS_NS) {
+        TypeRef::Simple(simple_type(st))
+    } else {
+        TypeRef::Any
+    };
+    ElementDecl {
+        name: element.attr("name").unwrap_or_default().into(),
+        occurs: occurs(element),
+        ty,
+    }
+}
+
+fn particle(element: &Element) -> Option<Particle> {
+    let items = || xs_children(element).filter_map(particle).collect();
+    match element.name() {
+        "element" => Some(Particle::Element(element_decl(element))),
+        "sequence" => Some(Particle::Sequence(items(), occurs(element))),
+        "choice" => Some(Particle::Choice(items(), occurs(element))),
+        _ => None,
+    }
+}
+
+fn complex_type(element: &Element) -> ComplexType {
+    let mut ct = ComplexType::default();
+    for child in xs_children(element) {
+        match child.name() {
+            "sequence" | "choice" => ct.content = particle(child),
+            "attribute" => ct.attributes.push(attribute_decl(child)),
+            "complexContent" | "simpleContent" => {
+                let Some(ext) = child.get_child("extension", XS_NS) else {
+                    continue;
+                };
+                let base = ext.attr("base").unwrap_or_default().to_string();
+                let inner = complex_type(ext);
+                ct.content = inner.content;
+                ct.attributes.extend(inner.attributes);
+                if child.name() == "complexContent" {
+                    ct.base = Some(base);
+                } else {
+                    ct.text = Some(SimpleType::Named(base));
+                }
+            }
+            _ => {}
+        }
+    }
+    ct
+}
+
+fn attribute_decl(element: &Element) -> AttributeDecl {
+    let ty = match element.get_child("simpleType", XS_NS) {
+        Some(st) => simple_type(st),
+        None => SimpleType::Named(element.attr("type").unwrap_or("xs:string").into()),
+    };
+    AttributeDecl {
+        name: element.attr("name").unwrap_or_default().into(),
+        ty,
+        required: element.attr("use") == Some("required"),
+        fixed: element.attr("fixed").map(Into::into),
+    }
+}
+
+/// number of values in an `ST_Array`, `None` if it is not an array of numbers
+///
+/// `g <count> <value>` repeats a value, as used by `DeltaX` and `DeltaY`.
+fn array_len(value: &str) -> Option<usize> {
+    let mut parts = value.split_whitespace();
+    let mut len = 0;
+    while let Some(part) = parts.next() {
+        if part == "g" {
+            let count = parts.next()?.parse::<usize>().ok()?;
+            parts.next()?.parse::<f64>().ok()?;
+            len += count;
+        } else {
+            part.parse::<f64>().ok()?;
+            len += 1;
+        }
+    }
+    Some(len)
+}
+
+fn simple_type(element: &Element) -> SimpleType {
+    let Some(restriction) = element.get_child("restriction", XS_NS) else {
+        return SimpleType::Named("xs:string".into());
+    };
+    let values = xs_children(restriction)
+        .filter(|c| c.name() == "enumeration")
+        .filter_map(|c| c.attr("value").map(Into::into))
+        .collect::<Vec<_>>();
+    if values.is_empty() {
+        SimpleType::Named(restriction.attr("base").unwrap_or("xs:string").into())
+    } else {
+        SimpleType::Enumeration(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(xml: &str) -> Vec<(String, String)> {
+        let root = xml.parse::<Element>().unwrap();
+        Schema::ofd().validate(&root)
+    }
+
+    #[test]
+    fn test_valid_page() {
+        let xml = r#"<Page xmlns="http://www.ofdspec.org/2016">
+            <Area><PhysicalBox>0 0 210 140</PhysicalBox></Area>
+            <Content><Layer ID="1">
+                <TextObject ID="2" Boundary="0 0 10 10" Font="3" Size="3">
+                    <TextCode X="0" Y="1">a</TextCode>
+                </TextObject>
+                <PathObject ID="4" Boundary="0 0 10 10">
+                    <AbbreviatedData>M 0 0 L 1 1</AbbreviatedData>
+                </PathObject>
+            </Layer></Content>
+        </Page>"#;
+        assert_eq!(validate(xml), vec![]);
+    }
+
+    #[test]
+    fn test_violations() {
+        let xml = r#"<Page xmlns="http://www.ofdspec.org/2016">
+            <Content><Layer ID="1" Foo="x">
+                <TextObject ID="2" Boundary="0 0 10" Size="3">
+                    <TextCode>a</TextCode>
+                </TextObject>
+            </Layer></Content>
+            <Area><PhysicalBox>0 0 210 140</PhysicalBox></Area>
+        </Page>"#;
+        let layer = "/Page/Content[1]/Layer[1]";
+        let text = "/Page/Content[1]/Layer[1]/TextObject[1]";
+        assert_eq!(
+            validate(xml),
+            vec![
+                ("/Page".into(), r#"unexpected element "Area""#.into()),
+                (layer.into(), r#"unexpected attribute "Foo""#.into()),
+                (
+                    text.into(),
+                    r#"attribute "Boundary": "0 0 10" is not a valid ST_Box"#.into()
+                ),
+                (text.into(), r#"missing required attribute "Font""#.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_array_values() {
+        assert_eq!(array_len("1 g 3 0.5 2"), Some(5));
+        assert_eq!(array_len(""), Some(0));
+        let xml = r#"<Page xmlns="http://www.ofdspec.org/2016">
+            <Area><PhysicalBox>0 0 210 140</PhysicalBox></Area>
+            <Content><Layer ID="1">
+                <TextObject ID="2" Boundary="0 0 10 10" Font="3" Size="3" CTM="1 0">
+                    <CGTransform CodePosition="0"><Glyphs>a b</Glyphs></CGTransform>
+                    <TextCode X="0" Y="1" DeltaX="1 g x">ab</TextCode>
+                    <TextCode X="0" Y="2" DeltaX="g 2 1.5" DeltaY="1 2">abc</TextCode>
+                </TextObject>
+            </Layer></Content>
+        </Page>"#;
+        let text = "/Page/Content[1]/Layer[1]/TextObject[1]";
+        assert_eq!(
+            validate(xml),
+            vec![
+                (
+                    text.into(),
+                    r#"attribute "CTM": "1 0" has not 6 numbers"#.into()
+                ),
+                (
+                    format!("{text}/CGTransform[1]/Glyphs[1]"),
+                    r#""a b" is not a valid ST_Array"#.into()
+                ),
+                (
+                    format!("{text}/TextCode[1]"),
+                    r#"attribute "DeltaX": "1 g x" is not a valid ST_Array"#.into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dates() {
+        assert!(is_date("2024-01-02"));
+        assert!(is_date("2024-01-02+08:00"));
+        assert!(!is_date("2024-01-0é"));
+        assert!(!is_date("2024-01-02é"));
+        let xml = r#"<OFD xmlns="http://www.ofdspec.org/2016" Version="1.0" DocType="OFD">
+            <DocBody><DocInfo><DocID>a</DocID><CreationDate>2024-01-0é</CreationDate></DocInfo>
+            <DocRoot>Doc_0/Document.xml</DocRoot></DocBody>
+        </OFD>"#;
+        assert_eq!(
+            validate(xml),
+            vec![(
+                "/OFD/DocBody[1]/DocInfo[1]/CreationDate[1]".into(),
+                r#""2024-01-0é" is not a valid xs:date"#.into()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_enumeration_and_fixed() {
+        let xml = r#"<OFD xmlns="http://www.ofdspec.org/2016" Version="2.0" DocType="PDF">
+            <DocBody><DocInfo><DocID>a</DocID></DocInfo><DocRoot>Doc_0/Document.xml</DocRoot></DocBody>
+        </OFD>"#;
+        let violations = validate(xml);
+        assert_eq!(violations.len(), 3);
+        assert!(violations[0].1.contains(r#"must be "1.0""#));
+        assert!(violations[2].1.contains(r#""PDF" is not one of"#));
+    }
+}
@@ -0,0 +1,161 @@
+use crate::container::{check_entry, Ofd, RawOfd};
+use crate::error::Result;
+use crate::limits::Limits;
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+use zip::ZipArchive;
+
+/// reader over the shared package bytes, each clone has its own position
+pub type SharedReader = Cursor<Arc<[u8]>>;
+
+/// a thread safe handle of an ofd package
+///
+/// the package is loaded into memory once and shared by all the clones.
+/// [Ofd] itself can not cross threads,
+/// so each worker gets its own view by [SharedOfd::ofd].
+#[derive(Clone)]
+pub struct SharedOfd {
+    zip_archive: ZipArchive<SharedReader>,
+    limits: Limits,
+    // bytes read by all the views, for `Limits::max_total_size`
+    bytes_read: Arc<AtomicU64>,
+}
+
+impl SharedOfd {
+    /// load an ofd file into memory
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_path_with_limits(path, Limits::default())
+    }
+
+    /// load an ofd file into memory, for untrusted packages
+    ///
+    /// the package size and the item count are checked right away,
+    /// the other limits apply to every view,
+    /// with `max_total_size` counting the bytes read by all of them.
+    pub fn from_path_with_limits(path: impl AsRef<Path>, limits: Limits) -> Result<Self> {
+        let mut file = File::open(path)?;
+        limits.check_package(file.metadata()?.len())?;
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes)?;
+        Self::from_bytes_with_limits(bytes, limits)
+    }
+
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>) -> Result<Self> {
+        Self::from_bytes_with_limits(bytes, Limits::default())
+    }
+
+    /// see [SharedOfd::from_path_with_limits]
+    pub fn from_bytes_with_limits(bytes: impl Into<Arc<[u8]>>, limits: Limits) -> Result<Self> {
+        let bytes = bytes.into();
+        limits.check_package(bytes.len() as u64)?;
+        let zip = ZipArchive::new(Cursor::new(bytes))?;
+        check_entry(&zip)?;
+        limits.check_items(zip.len())?;
+        Ok(Self {
+            zip_archive: zip,
+            limits,
+            bytes_read: Arc::default(),
+        })
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// a new view of the package for the current thread
+    ///
+    /// the zip central directory is shared, parsed items are cached per view.
+    pub fn ofd(&self) -> Ofd<ZipArchive<SharedReader>> {
+        let raw = RawOfd::new(self.zip_archive.clone());
+        Ofd::from_raw(raw.with_limits(self.limits, self.bytes_read.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use eyre::Result;
+    use std::thread;
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SharedOfd>();
+    }
+
+    #[test]
+    fn test_limits() -> Result<()> {
+        let path = "../samples/000.ofd";
+        let size = std::fs::metadata(path)?.len();
+        let limits = Limits {
+            max_package_size: Some(size - 1),
+            ..Default::default()
+        };
+        assert!(matches!(
+            SharedOfd::from_path_with_limits(path, limits),
+            Err(Error::PackageTooLarge { .. })
+        ));
+        let count = SharedOfd::from_path(path)?.ofd().item_names().len();
+        let limits = Limits {
+            max_items: Some(count - 1),
+            ..Default::default()
+        };
+        assert!(matches!(
+            SharedOfd::from_path_with_limits(path, limits),
+            Err(Error::TooManyItems { .. })
+        ));
+
+        // the bytes read are counted across the views
+        let item = "Doc_0/Signs/Sign_0/SignedValue.dat";
+        let item_size = SharedOfd::from_path(path)?.ofd().bytes(item)?.len() as u64;
+        let limits = Limits {
+            max_total_size: Some(item_size + 1),
+            ..Default::default()
+        };
+        let shared = SharedOfd::from_path_with_limits(path, limits)?;
+        assert_eq!(shared.ofd().limits(), limits);
+        shared.ofd().bytes(item)?;
+        assert!(matches!(
+            shared.ofd().bytes(item),
+            Err(Error::TotalSizeExceeded { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_pages_concurrently() -> Result<()> {
+        let shared = SharedOfd::from_path("../samples/002.ofd")?;
+        let doc = shared.ofd().document_by_index(0)?;
+        let page_count = doc.pages.page.len();
+        assert!(page_count > 1);
+
+        let layers = thread::scope(|s| {
+            let handles = (0..page_count)
+                .map(|page_index| {
+                    let shared = shared.clone();
+                    s.spawn(move || -> crate::error::Result<usize> {
+                        let page = shared.ofd().page_by_index(0, page_index)?;
+                        let content = page.content.content.as_ref();
+                        Ok(content.map_or(0, |c| c.layer.len()))
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap())
+                .collect::<crate::error::Result<Vec<_>>>()
+        })?;
+
+        let ofd = shared.ofd();
+        for (page_index, count) in layers.into_iter().enumerate() {
+            let page = ofd.page_by_index(0, page_index)?;
+            let expected = page.content.content.as_ref().map_or(0, |c| c.layer.len());
+            assert_eq!(count, expected);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,257 @@
+use crate::error::{Error, Result};
+use crate::writer::{item_name, OfdWriter};
+use relative_path::{Component, RelativePath};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+/// where the items of an ofd package are stored
+///
+/// item names are relative to the package root, separated by `/`,
+/// without a leading `/`.
+pub trait Storage {
+    /// names of all the items in the package
+    fn item_names(&self) -> Vec<String>;
+
+    /// does the package contain an item
+    fn contains(&self, name: &str) -> bool;
+
+    /// open an item for reading
+    fn open(&mut self, name: &str) -> Result<Box<dyn Read + '_>>;
+
+    /// stored size of a compressed item, `None` if unknown or not compressed
+    fn compressed_size(&mut self, _name: &str) -> Option<u64> {
+        None
+    }
+
+    /// copy an item into an ofd package
+    ///
+    /// a zip storage copies the compressed data as it is.
+    fn copy_to<W: Write + Seek>(&mut self, name: &str, writer: &mut OfdWriter<W>) -> Result<()> {
+        let mut buf = Vec::new();
+        self.open(name)?.read_to_end(&mut buf)?;
+        writer.write_bytes(name, &buf)
+    }
+}
+
+impl<R: Read + Seek> Storage for ZipArchive<R> {
+    fn item_names(&self) -> Vec<String> {
+        self.file_names().map(str::to_owned).collect()
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.index_for_name(name).is_some()
+    }
+
+    fn open(&mut self, name: &str) -> Result<Box<dyn Read + '_>> {
+        let file = self
+            .by_name(name)
+            .map_err(|e| Error::OpenZipError(e, name.into()))?;
+        Ok(Box::new(file))
+    }
+
+    fn compressed_size(&mut self, name: &str) -> Option<u64> {
+        self.by_name(name).ok().map(|f| f.compressed_size())
+    }
+
+    fn copy_to<W: Write + Seek>(&mut self, name: &str, writer: &mut OfdWriter<W>) -> Result<()> {
+        let index = self
+            .index_for_name(name)
+            .ok_or_else(|| Error::OpenZipError(ZipError::FileNotFound, name.into()))?;
+        let file = self.by_index_raw(index)?;
+        writer.raw_copy_file(file)
+    }
+}
+
+/// an extracted ofd package on disk
+#[derive(Debug, Clone)]
+pub struct DirStorage {
+    root: PathBuf,
+}
+
+impl DirStorage {
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    /// the directory holding `OFD.xml`
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// file path of an item, items outside the root are rejected
+    fn file_path(&self, name: &str) -> Option<PathBuf> {
+        let name = RelativePath::new(name);
+        let inside = name
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        inside.then(|| name.to_path(&self.root))
+    }
+
+    fn walk(&self, dir: &Path, names: &mut Vec<String>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        let mut entries = entries.filter_map(|e| e.ok()).collect::<Vec<_>>();
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let path = entry.path();
+            if path.is_dir() {
+                self.walk(&path, names);
+            } else if let Ok(rel) = path.strip_prefix(&self.root) {
+                let name = rel
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                names.push(name);
+            }
+        }
+    }
+}
+
+impl Storage for DirStorage {
+    fn item_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.walk(&self.root, &mut names);
+        names
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.file_path(name).is_some_and(|p| p.is_file())
+    }
+
+    fn open(&mut self, name: &str) -> Result<Box<dyn Read + '_>> {
+        let path = self
+            .file_path(name)
+            .filter(|p| p.is_file())
+            .ok_or_else(|| Error::ItemNotFound(name.into()))?;
+        let file = File::open(path)?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// an ofd package held in memory, item name to bytes
+#[derive(Debug, Clone, Default)]
+pub struct MemStorage {
+    items: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add or replace an item
+    pub fn insert(&mut self, path: impl AsRef<str>, bytes: impl Into<Vec<u8>>) {
+        self.items.insert(item_name(path.as_ref()), bytes.into());
+    }
+
+    /// remove an item
+    pub fn remove(&mut self, path: impl AsRef<str>) -> Option<Vec<u8>> {
+        self.items.remove(&item_name(path.as_ref()))
+    }
+}
+
+impl<S: AsRef<str>, B: Into<Vec<u8>>> FromIterator<(S, B)> for MemStorage {
+    fn from_iter<T: IntoIterator<Item = (S, B)>>(iter: T) -> Self {
+        let mut storage = Self::new();
+        for (path, bytes) in iter {
+            storage.insert(path, bytes);
+        }
+        storage
+    }
+}
+
+impl Storage for MemStorage {
+    fn item_names(&self) -> Vec<String> {
+        self.items.keys().cloned().collect()
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.items.contains_key(name)
+    }
+
+    fn open(&mut self, name: &str) -> Result<Box<dyn Read + '_>> {
+        let bytes = self
+            .items
+            .get(name)
+            .ok_or_else(|| Error::ItemNotFound(name.into()))?;
+        Ok(Box::new(Cursor::new(bytes)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_dir, from_path, from_storage, Ofd};
+    use eyre::Result;
+
+    fn assert_same<A: Storage, B: Storage>(a: &Ofd<A>, b: &Ofd<B>) -> Result<()> {
+        assert_eq!(format!("{:?}", a.entry()?), format!("{:?}", b.entry()?));
+        let doc = a.document_by_index(0)?;
+        assert_eq!(
+            format!("{:?}", doc),
+            format!("{:?}", b.document_by_index(0)?)
+        );
+        for page_index in 0..doc.pages.page.len() {
+            let page = a.page_by_index(0, page_index)?;
+            let expected = b.page_by_index(0, page_index)?;
+            assert_eq!(format!("{:?}", page), format!("{:?}", expected));
+        }
+        let path = "Doc_0/Signs/Sign_0/SignedValue.dat";
+        assert_eq!(a.bytes(path)?, b.bytes(path)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dir_storage() -> Result<()> {
+        let zip = from_path("../samples/000.ofd")?;
+        let root = std::env::temp_dir().join("ofd_rw_test_dir_storage");
+        let _ = fs::remove_dir_all(&root);
+        for name in zip.item_names().iter().filter(|n| !n.ends_with('/')) {
+            let path = RelativePath::new(name).to_path(&root);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, zip.bytes(name.as_str())?)?;
+        }
+        let dir = from_dir(&root)?;
+        assert_same(&zip, &dir)?;
+        assert!(dir.bytes("../ofd_rw_test_dir_storage/OFD.xml").is_err());
+
+        // the extracted sample is formatted, but holds the same items
+        let mut names = zip.item_names();
+        names.retain(|n| !n.ends_with('/'));
+        names.sort();
+        assert_eq!(names, from_dir("../samples/000")?.item_names());
+        fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_mem_storage() -> Result<()> {
+        let zip = from_path("../samples/000.ofd")?;
+        let mem = zip
+            .item_names()
+            .into_iter()
+            .map(|name| Ok((name.clone(), zip.bytes(name)?)))
+            .collect::<Result<MemStorage>>()?;
+        let mem = from_storage(mem)?;
+        assert_same(&zip, &mem)?;
+        assert!(matches!(
+            mem.bytes("not_exists"),
+            Err(Error::ItemNotFound(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_not_found() {
+        let res = from_storage(MemStorage::new());
+        assert!(matches!(res, Err(Error::OfdEntryNotFound)));
+    }
+}
@@ -0,0 +1,418 @@
+use crate::container::{inner_resolve, Ofd};
+use crate::error::Result;
+use crate::id_index::{index_document, IdIndex, IdTarget, IndexLoader, ResourceKind};
+use crate::storage::Storage;
+use ofd_base::common::CtColor;
+use ofd_base::file::document::DocumentXmlFile;
+use ofd_base::file::page::VtGraphicUnit;
+use ofd_base::file::res::{Resource, ResourceXmlFile};
+use ofd_base::file::signature::{SignatureXmlFile, SignaturesXmlFile};
+use ofd_base::{StArray, StBox, StId, StLoc, StRefId};
+use relative_path::RelativePathBuf;
+use serde::de::DeserializeOwned;
+use std::rc::Rc;
+use thiserror::Error;
+
+/// a spec violation found by [Ofd::validate]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    /// the item holding the problem
+    pub path: RelativePathBuf,
+    pub kind: IssueKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum IssueKind {
+    #[error(r#"{name} points to a missing item "{target}""#)]
+    MissingItem {
+        /// element or attribute holding the location, e.g. `BaseLoc`
+        name: &'static str,
+        target: RelativePathBuf,
+    },
+
+    #[error(r#"{name} "{loc}" is not a valid location in the package"#)]
+    InvalidLoc { name: &'static str, loc: String },
+
+    #[error("unreadable item: {0}")]
+    Unreadable(String),
+
+    #[error("{name} refers to an undefined id {id}")]
+    MissingId { name: &'static str, id: StRefId },
+
+    #[error("id {id} is already used by {first:?}")]
+    DuplicateId { id: StId, first: IdTarget },
+
+    #[error("id {0} exceeds MaxUnitID")]
+    ExceedsMaxUnitId(StId),
+
+    #[error("boundary of object {id} has a negative size: {boundary}")]
+    NegativeBoundary { id: StId, boundary: StBox },
+
+    #[error("CTM of object {id} has {len} elements, expect 6")]
+    InvalidCtm { id: StId, len: usize },
+}
+
+impl Issue {
+    fn new(path: &RelativePathBuf, kind: IssueKind) -> Self {
+        Self {
+            path: path.clone(),
+            kind,
+        }
+    }
+}
+
+impl<S: Storage> Ofd<S> {
+    /// check the structure of the package
+    ///
+    /// problems in the items are collected as [Issue]s,
+    /// an error is returned only if `OFD.xml` itself can not be read.
+    pub fn validate(&self) -> Result<Vec<Issue>> {
+        let mut validator = Validator {
+            ofd: self,
+            issues: vec![],
+        };
+        let entry = self.entry()?;
+        let entry_path = entry.path().clone();
+        for doc_body in entry.doc_body.iter() {
+            if let Some(p) = &doc_body.signatures {
+                validator.validate_signatures(&entry_path, p);
+            }
+            for v in doc_body.versions.iter().flat_map(|v| v.version.iter()) {
+                validator.check_loc(&entry_path, "BaseLoc", &v.base_loc);
+            }
+            if let Some(p) = &doc_body.doc_root {
+                validator.validate_document(&entry_path, p);
+            }
+        }
+        Ok(validator.issues)
+    }
+}
+
+struct Validator<'a, S> {
+    ofd: &'a Ofd<S>,
+    issues: Vec<Issue>,
+}
+
+impl<S: Storage> Validator<'_, S> {
+    fn issue(&mut self, path: &RelativePathBuf, kind: IssueKind) {
+        self.issues.push(Issue::new(path, kind));
+    }
+
+    /// resolve a location, `None` if the item is missing
+    fn check_loc(
+        &mut self,
+        parent: &RelativePathBuf,
+        name: &'static str,
+        loc: &StLoc,
+    ) -> Option<RelativePathBuf> {
+        let target = self.resolve(parent, name, loc)?;
+        if self.ofd.contains(target.as_str()) {
+            Some(target)
+        } else {
+            self.issue(parent, IssueKind::MissingItem { name, target });
+            None
+        }
+    }
+
+    /// resolve and parse an xml item
+    fn load<T>(
+        &mut self,
+        parent: &RelativePathBuf,
+        name: &'static str,
+        loc: &StLoc,
+    ) -> Option<(RelativePathBuf, Rc<T>)>
+    where
+        T: 'static + DeserializeOwned,
+    {
+        let path = self.check_loc(parent, name, loc)?;
+        let xml = self.parse(&path)?;
+        Some((path, xml))
+    }
+
+    fn parse<T>(&mut self, path: &RelativePathBuf) -> Option<Rc<T>>
+    where
+        T: 'static + DeserializeOwned,
+    {
+        match self.ofd.xml_by_path::<T>(path.as_str()) {
+            Ok(xml) => Some(xml),
+            Err(e) => {
+                self.issue(path, IssueKind::Unreadable(e.to_string()));
+                None
+            }
+        }
+    }
+
+    fn validate_signatures(&mut self, entry_path: &RelativePathBuf, loc: &StLoc) {
+        let Some((path, sigs)) = self.load::<SignaturesXmlFile>(entry_path, "Signatures", loc)
+        else {
+            return;
+        };
+        for sig in sigs.signature.iter().flatten() {
+            let Some((sig_path, xml)) =
+                self.load::<SignatureXmlFile>(&path, "BaseLoc", &sig.base_loc)
+            else {
+                continue;
+            };
+            self.check_loc(&sig_path, "SignedValue", &xml.signed_value);
+            for r in xml.signed_info.references.references.iter() {
+                self.check_loc(&sig_path, "FileRef", &r.file_ref);
+            }
+            if let Some(seal) = &xml.signed_info.seal {
+                self.check_loc(&sig_path, "Seal", &seal.base_loc);
+            }
+        }
+    }
+
+    fn validate_document(&mut self, entry_path: &RelativePathBuf, loc: &StLoc) {
+        let Some((doc_path, doc)) = self.load::<DocumentXmlFile>(entry_path, "DocRoot", loc) else {
+            return;
+        };
+        // items failing to load are recorded as issues, so indexing never fails
+        let Ok(indexed) = index_document(&doc_path, &doc, self) else {
+            return;
+        };
+        let index = indexed.index;
+        if let Some((path, annots)) = &indexed.annotations {
+            for page in annots.page.iter().flatten() {
+                if index.page_index_by_id(page.page_id).is_none() {
+                    let id = page.page_id;
+                    self.issue(path, IssueKind::MissingId { name: "PageID", id });
+                }
+            }
+        }
+        for (name, loc) in [
+            ("CustomTags", &doc.custom_tags),
+            ("Attachments", &doc.attachments),
+            ("Extensions", &doc.extensions),
+        ] {
+            if let Some(loc) = loc {
+                self.check_loc(&doc_path, name, loc);
+            }
+        }
+
+        for (id, target) in index.duplicates() {
+            let path = target_path(target).unwrap_or(&doc_path);
+            let first = index.get(*id).cloned().expect("duplicated id is indexed");
+            self.issue(path, IssueKind::DuplicateId { id: *id, first });
+        }
+        for id in index.exceeding_max_unit_id() {
+            let path = index.get(id).and_then(target_path).unwrap_or(&doc_path);
+            self.issue(path, IssueKind::ExceedsMaxUnitId(id));
+        }
+
+        let mut checker = RefChecker {
+            index: &index,
+            issues: &mut self.issues,
+            path: &doc_path,
+        };
+        checker.resource(
+            doc.common_data.default_cs,
+            "DefaultCS",
+            ResourceKind::ColorSpace,
+        );
+        for (path, xml) in indexed.resources.iter() {
+            self.validate_resource(&index, path, xml);
+        }
+        for (path, xml) in indexed.pages.iter() {
+            let mut checker = RefChecker {
+                index: &index,
+                issues: &mut self.issues,
+                path,
+            };
+            for tpl in xml.template.iter().flatten() {
+                if !matches!(index.get(tpl.template_id), Some(IdTarget::Template { .. })) {
+                    let id = tpl.template_id;
+                    checker.missing("TemplateID", id);
+                }
+            }
+            for layer in xml.content.iter().flat_map(|c| c.layer.iter()) {
+                checker.resource(layer.draw_param, "DrawParam", ResourceKind::DrawParam);
+                for object in layer.objects.iter().flatten() {
+                    checker.object(object);
+                }
+            }
+        }
+        for (path, xml) in indexed.annotation_files.iter() {
+            let mut checker = RefChecker {
+                index: &index,
+                issues: &mut self.issues,
+                path,
+            };
+            let objects = xml
+                .annot
+                .iter()
+                .flat_map(|a| a.appearance.objects.iter().flatten());
+            for object in objects {
+                checker.object(object);
+            }
+        }
+    }
+
+    fn validate_resource(
+        &mut self,
+        index: &IdIndex,
+        path: &RelativePathBuf,
+        xml: &ResourceXmlFile,
+    ) {
+        let base = &xml.base_loc;
+        for resource in xml.resources.iter().flatten() {
+            match resource {
+                Resource::Fonts(fonts) => {
+                    for loc in fonts.fonts.iter().filter_map(|f| f.font_file.as_ref()) {
+                        self.check_file(path, "FontFile", &base.join(loc));
+                    }
+                }
+                Resource::MultiMedias(medias) => {
+                    for media in medias.multi_medias.iter() {
+                        self.check_file(path, "MediaFile", &base.join(&media.media_file));
+                    }
+                }
+                Resource::DrawParams(dps) => {
+                    let mut checker = RefChecker {
+                        index,
+                        issues: &mut self.issues,
+                        path,
+                    };
+                    for dp in dps.draw_params.iter() {
+                        checker.resource(dp.relative, "Relative", ResourceKind::DrawParam);
+                        checker.color(dp.stroke_color.as_ref());
+                        checker.color(dp.fill_color.as_ref());
+                    }
+                }
+                Resource::ColorSpaces(_) | Resource::CompositeGraphicUnits(_) => {}
+            }
+        }
+    }
+
+    /// files in a resource are located against `BaseLoc` of the resource file
+    fn check_file(&mut self, path: &RelativePathBuf, name: &'static str, loc: &StLoc) {
+        let Some(target) = self.resolve(path, name, loc) else {
+            return;
+        };
+        if !self.ofd.contains(target.as_str()) {
+            self.issue(path, IssueKind::MissingItem { name, target });
+        }
+    }
+
+    fn resolve(
+        &mut self,
+        parent: &RelativePathBuf,
+        name: &'static str,
+        loc: &StLoc,
+    ) -> Option<RelativePathBuf> {
+        match inner_resolve(parent, loc) {
+            Ok(target) => Some(target),
+            Err(_) => {
+                let loc = loc.to_string_lossy().into();
+                self.issue(parent, IssueKind::InvalidLoc { name, loc });
+                None
+            }
+        }
+    }
+}
+
+impl<S: Storage> IndexLoader for Validator<'_, S> {
+    fn locate(
+        &mut self,
+        parent: &RelativePathBuf,
+        name: &'static str,
+        loc: &StLoc,
+    ) -> Result<Option<RelativePathBuf>> {
+        Ok(self.check_loc(parent, name, loc))
+    }
+
+    fn load<T>(&mut self, path: &RelativePathBuf) -> Result<Option<Rc<T>>>
+    where
+        T: 'static + DeserializeOwned,
+    {
+        Ok(self.parse(path))
+    }
+}
+
+fn target_path(target: &IdTarget) -> Option<&RelativePathBuf> {
+    match target {
+        IdTarget::Page { .. } => None,
+        IdTarget::Template { path }
+        | IdTarget::Layer { path }
+        | IdTarget::Object { path }
+        | IdTarget::Resource { path, .. }
+        | IdTarget::Annotation { path }
+        | IdTarget::AppearanceObject { path } => Some(path),
+    }
+}
+
+/// checks the references from objects in one item
+struct RefChecker<'a> {
+    index: &'a IdIndex,
+    issues: &'a mut Vec<Issue>,
+    path: &'a RelativePathBuf,
+}
+
+impl RefChecker<'_> {
+    fn missing(&mut self, name: &'static str, id: StRefId) {
+        let kind = IssueKind::MissingId { name, id };
+        self.issues.push(Issue::new(self.path, kind));
+    }
+
+    fn resource(&mut self, id: Option<StRefId>, name: &'static str, kind: ResourceKind) {
+        let Some(id) = id else {
+            return;
+        };
+        match self.index.get(id) {
+            Some(IdTarget::Resource { kind: k, .. }) if *k == kind => {}
+            _ => self.missing(name, id),
+        }
+    }
+
+    fn color(&mut self, color: Option<&CtColor>) {
+        if let Some(color) = color {
+            self.resource(color.color_space, "ColorSpace", ResourceKind::ColorSpace);
+        }
+    }
+
+    fn graphic(&mut self, id: StId, boundary: &StBox, ctm: Option<&StArray<f32>>) {
+        if boundary.w < 0.0 || boundary.h < 0.0 {
+            let boundary = *boundary;
+            let kind = IssueKind::NegativeBoundary { id, boundary };
+            self.issues.push(Issue::new(self.path, kind));
+        }
+        if let Some(ctm) = ctm.filter(|c| c.len() != 6) {
+            let kind = IssueKind::InvalidCtm { id, len: ctm.len() };
+            self.issues.push(Issue::new(self.path, kind));
+        }
+    }
+
+    fn object(&mut self, object: &VtGraphicUnit) {
+        self.resource(object.draw_param(), "DrawParam", ResourceKind::DrawParam);
+        match object {
+            VtGraphicUnit::TextObject(t) => {
+                self.graphic(t.id, &t.boundary, t.ctm.as_ref());
+                self.resource(Some(t.font), "Font", ResourceKind::Font);
+                self.color(t.fill_color.as_ref());
+                self.color(t.stroke_color.as_ref());
+            }
+            VtGraphicUnit::PathObject(p) => {
+                self.graphic(p.id, &p.boundary, p.ctm.as_ref());
+                self.color(p.fill_color.as_ref());
+                self.color(p.stroke_color.as_ref());
+            }
+            VtGraphicUnit::ImageObject(i) => {
+                self.graphic(i.id, &i.boundary, i.ctm.as_ref());
+                let kind = ResourceKind::MultiMedia;
+                self.resource(Some(i.resource_id), "ResourceID", kind);
+                self.resource(i.substitution, "Substitution", kind);
+                self.resource(i.image_mask, "ImageMask", kind);
+            }
+            VtGraphicUnit::CompositeObject(c) => {
+                self.graphic(c.id, &c.boundary, c.ctm.as_ref());
+                let kind = ResourceKind::CompositeGraphicUnit;
+                self.resource(Some(c.resource_id), "ResourceID", kind);
+            }
+            VtGraphicUnit::PageBlock(b) => {
+                for object in b.objects.iter().flatten() {
+                    self.object(object);
+                }
+            }
+        }
+    }
+}
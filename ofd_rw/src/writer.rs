@@ -0,0 +1,277 @@
+use crate::error::Result;
+use crate::OFD_NS;
+use ofd_base::file::annotation::{AnnotationXmlFile, AnnotationsXmlFile};
+use ofd_base::file::attachment::AttachmentsXmlFile;
+use ofd_base::file::custom_tag::CustomTagsXmlFile;
+use ofd_base::file::document::DocumentXmlFile;
+use ofd_base::file::extension::ExtensionXmlFile;
+use ofd_base::file::ofd::OfdXmlFile;
+use ofd_base::file::page::PageXmlFile;
+use ofd_base::file::res::ResourceXmlFile;
+use ofd_base::file::signature::{SignatureXmlFile, SignaturesXmlFile};
+use ofd_base::file::version::VersionXmlFile;
+use relative_path::RelativePath;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Seek, Write};
+use std::path::Path;
+use tracing::debug;
+use xdom::ser::XmlSer;
+use zip::read::ZipFile;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// an xml file which can be written into an ofd package
+///
+/// the root element name can not be inferred from the struct name,
+/// so each xml file declares its own.
+pub trait OfdXml: Serialize {
+    /// name of the root element, without prefix
+    const ROOT_NAME: &'static str;
+}
+
+macro_rules! impl_ofd_xml {
+    ($($ty:ty => $name:literal),* $(,)?) => {
+        $(
+            impl OfdXml for $ty {
+                const ROOT_NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+impl_ofd_xml! {
+    OfdXmlFile => "OFD",
+    DocumentXmlFile => "Document",
+    PageXmlFile => "Page",
+    ResourceXmlFile => "Res",
+    AnnotationsXmlFile => "Annotations",
+    AnnotationXmlFile => "PageAnnot",
+    SignaturesXmlFile => "Signatures",
+    SignatureXmlFile => "Signature",
+    AttachmentsXmlFile => "Attachments",
+    CustomTagsXmlFile => "CustomTags",
+    ExtensionXmlFile => "Extensions",
+    VersionXmlFile => "DocVersion",
+}
+
+/// serialize an xml file into bytes, with xml declaration
+pub fn to_xml_bytes<T: OfdXml>(value: &T) -> Result<Vec<u8>> {
+    let ser = XmlSer::builder()
+        .name(T::ROOT_NAME)
+        .ns(OFD_NS)
+        .prefix(Some("ofd".into()))
+        .build()?;
+    let root = ser.ser_to_element(value)?;
+    let mut buf = Vec::new();
+    root.write_to_decl(&mut buf)?;
+    Ok(buf)
+}
+
+/// writes an ofd package
+///
+/// the caller decides the package layout,
+/// all paths are relative to the package root.
+/// `OFD.xml` is written by [OfdWriter::write_entry].
+pub struct OfdWriter<W: Write + Seek> {
+    zip_writer: ZipWriter<W>,
+    options: SimpleFileOptions,
+}
+
+impl OfdWriter<BufWriter<File>> {
+    /// create an ofd file at `path`
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write + Seek> OfdWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            zip_writer: ZipWriter::new(writer),
+            options: SimpleFileOptions::default(),
+        }
+    }
+
+    /// write the `OFD.xml` entry
+    pub fn write_entry(&mut self, ofd: &OfdXmlFile) -> Result<()> {
+        self.write_xml("OFD.xml", ofd)
+    }
+
+    /// serialize `value` and write it to `path`
+    pub fn write_xml<T: OfdXml>(&mut self, path: impl AsRef<str>, value: &T) -> Result<()> {
+        let bytes = to_xml_bytes(value)?;
+        self.write_bytes(path, &bytes)
+    }
+
+    /// write raw bytes to `path`, fonts, images and so on
+    pub fn write_bytes(&mut self, path: impl AsRef<str>, bytes: &[u8]) -> Result<()> {
+        let name = item_name(path.as_ref());
+        debug!("writing item into zip: {}", name);
+        self.zip_writer.start_file(name, self.options)?;
+        self.zip_writer.write_all(bytes)?;
+        Ok(())
+    }
+
+    /// copy a compressed item from another zip as it is
+    pub(crate) fn raw_copy_file(&mut self, file: ZipFile) -> Result<()> {
+        debug!("copying item into zip: {}", file.name());
+        self.zip_writer.raw_copy_file(file)?;
+        Ok(())
+    }
+
+    /// finish the package and return the underlying writer
+    pub fn finish(self) -> Result<W> {
+        let w = self.zip_writer.finish()?;
+        Ok(w)
+    }
+}
+
+/// zip item names never start with `/`
+pub(crate) fn item_name(path: &str) -> String {
+    RelativePath::new(path.trim_start_matches('/'))
+        .normalize()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_bytes;
+    use eyre::Result;
+    use ofd_base::file::document::{CommonData, CtPageArea, Page, Pages};
+    use ofd_base::file::ofd::{CtDocInfo, DocBody};
+    use ofd_base::file::page::{Content, Layer};
+    use ofd_base::file::res::{Font, Fonts, Resource};
+    use ofd_base::StBox;
+    use std::io::Cursor;
+
+    fn new_ofd() -> (OfdXmlFile, DocumentXmlFile, PageXmlFile, ResourceXmlFile) {
+        let ofd = OfdXmlFile {
+            version: "1.1".into(),
+            doc_type: "OFD".into(),
+            doc_body: vec![DocBody {
+                doc_info: CtDocInfo {
+                    doc_id: Some("abc".into()),
+                    ..Default::default()
+                },
+                doc_root: Some("Doc_0/Document.xml".into()),
+                versions: None,
+                signatures: None,
+            }],
+        };
+        let doc = DocumentXmlFile {
+            common_data: CommonData {
+                max_unit_id: 3,
+                page_area: CtPageArea {
+                    physical_box: StBox::from((0.0, 0.0, 210.0, 297.0)),
+                    application_box: None,
+                    content_box: None,
+                    bleed_box: None,
+                },
+                public_res: Some(vec!["PublicRes.xml".into()]),
+                document_res: None,
+                template_page: None,
+                default_cs: None,
+            },
+            pages: Pages {
+                page: vec![Page {
+                    id: 1,
+                    base_loc: "Pages/Page_0/Content.xml".into(),
+                }],
+            },
+            outlines: None,
+            permissions: None,
+            actions: None,
+            v_preferences: None,
+            bookmarks: None,
+            annotations: None,
+            custom_tags: None,
+            attachments: None,
+            extensions: None,
+        };
+        let page = PageXmlFile {
+            area: None,
+            template: None,
+            page_res: None,
+            content: Some(Content {
+                layer: vec![Layer {
+                    r#type: None,
+                    draw_param: None,
+                    id: 2,
+                    objects: None,
+                }],
+            }),
+        };
+        let res = ResourceXmlFile {
+            base_loc: "Res".into(),
+            resources: Some(vec![Resource::Fonts(Fonts {
+                fonts: vec![Font {
+                    id: 3,
+                    font_name: "宋体".into(),
+                    family_name: None,
+                    charset: None,
+                    italic: None,
+                    bold: None,
+                    serif: None,
+                    fixed_width: None,
+                    font_file: Some("font_3.ttf".into()),
+                }],
+            })]),
+        };
+        (ofd, doc, page, res)
+    }
+
+    #[test]
+    fn test_write_and_read_back() -> Result<()> {
+        let (ofd, doc, page, res) = new_ofd();
+        let mut writer = OfdWriter::new(Cursor::new(Vec::new()));
+        writer.write_entry(&ofd)?;
+        writer.write_xml("Doc_0/Document.xml", &doc)?;
+        writer.write_xml("/Doc_0/Pages/Page_0/Content.xml", &page)?;
+        writer.write_xml("Doc_0/PublicRes.xml", &res)?;
+        writer.write_bytes("Doc_0/Res/font_3.ttf", b"not a real font")?;
+        let bytes = writer.finish()?.into_inner();
+
+        let ofd = from_bytes(bytes)?;
+        assert_eq!(ofd.entry()?.doc_body.len(), 1);
+        let doc = ofd.document_by_index(0)?;
+        assert_eq!(doc.common_data.max_unit_id, 3);
+        let page = ofd.page_by_index(0, 0)?;
+        assert_eq!(page.content.content.as_ref().unwrap().layer[0].id, 2);
+        let resources = ofd.resources_for_page(0, 0)?;
+        let (_, font) = resources.get_font_by_id(3).unwrap();
+        assert_eq!(font.font_name, "宋体");
+        assert_eq!(ofd.bytes("Doc_0/Res/font_3.ttf")?, b"not a real font");
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrite_sample() -> Result<()> {
+        let src = crate::from_path("../samples/000.ofd")?;
+        let mut writer = OfdWriter::new(Cursor::new(Vec::new()));
+        writer.write_entry(&src.entry()?.content)?;
+        let doc = src.document_by_index(0)?;
+        writer.write_xml(doc.path().as_str(), &*doc.content)?;
+        let page = src.page_by_index(0, 0)?;
+        writer.write_xml(page.path().as_str(), &*page.content)?;
+        for tpl in src.templates_for_page(0, 0)? {
+            writer.write_xml(tpl.path().as_str(), &*tpl.content)?;
+        }
+        for name in src.item_names() {
+            if !name.ends_with(".xml") {
+                writer.write_bytes(&name, &src.bytes(name.as_str())?)?;
+            }
+        }
+        let bytes = writer.finish()?.into_inner();
+
+        let dst = from_bytes(bytes)?;
+        let page = dst.page_by_index(0, 0)?;
+        let expected = src.page_by_index(0, 0)?;
+        let layers = |p: &PageXmlFile| p.content.as_ref().map(|c| c.layer.len());
+        assert_eq!(layers(&page), layers(&expected));
+        assert_eq!(dst.templates_for_page(0, 0)?.len(), 1);
+        Ok(())
+    }
+}
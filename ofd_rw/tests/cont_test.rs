@@ -1,5 +1,19 @@
 use eyre::Result;
-use ofd_rw::from_path;
+use ofd_base::common::{ActionType, Actions, CtAction, CtDest, Event, VtTo};
+use ofd_base::file::document::{Bookmarks, CtBookmark, CtOutlineElem, Outlines};
+use ofd_base::file::extension::Prop;
+use ofd_base::file::ofd::{Version, Versions};
+use ofd_base::file::page::VtGraphicUnit;
+use ofd_base::file::res::{Resource, ResourceXmlFile};
+use ofd_base::file::version::{File, FileList, VersionXmlFile};
+use ofd_rw::error::Error;
+use ofd_rw::{
+    from_bytes, from_path, from_path_mmap, from_reader, parse_page_ranges, IdTarget, IssueKind,
+    Limits, NavTarget, OfdEditor, RecoveryKind, ResourceKind, SharedOfd,
+};
+use relative_path::RelativePathBuf;
+use std::io::Cursor;
+use std::rc::Rc;
 // use
 #[test]
 fn test() -> Result<()> {
@@ -9,3 +23,800 @@ fn test() -> Result<()> {
     dbg!(e);
     Ok(())
 }
+
+#[test]
+fn test_attachments() -> Result<()> {
+    let ofd = from_path("../samples/000.ofd")?;
+    let attachments = ofd.attachments_for_doc(0)?;
+    assert_eq!(attachments.len(), 1);
+    let attachment = &attachments[0];
+    assert_eq!(attachment.name, "original_invoice");
+    assert_eq!(attachment.format.as_deref(), Some("xml"));
+    assert_eq!(
+        attachment.path().as_str(),
+        "Doc_0/Attachs/original_invoice.xml"
+    );
+
+    let mut buf = Vec::new();
+    let n = ofd.copy_item_to(attachment.path().as_str(), &mut buf)?;
+    assert_eq!(n as usize, buf.len());
+    assert_eq!(buf, ofd.bytes(attachment.path().as_str())?);
+    assert!(buf.starts_with(b"<?xml"));
+
+    let ofd = from_path("../samples/002.ofd")?;
+    assert!(ofd.attachments_for_doc(0)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_custom_tags() -> Result<()> {
+    let ofd = from_path("../samples/000.ofd")?;
+    let tags = ofd.custom_tags_for_doc(0)?.unwrap();
+    assert_eq!(tags.path().as_str(), "Doc_0/Tags/CustomTags.xml");
+
+    let files = ofd.custom_tag_files_for_doc(0)?;
+    assert_eq!(files.len(), 1);
+    let file = &files[0];
+    assert_eq!(file.path().as_str(), "Doc_0/Tags/CustomTag.xml");
+    assert!(file.schema_loc.is_none());
+
+    let invoice_no = file.root.find("InvoiceNo").unwrap();
+    assert_eq!(ofd.tagged_text(0, invoice_no)?, "72262900");
+    let amount = file.root.find("TaxInclusiveTotalAmount").unwrap();
+    assert_eq!(ofd.tagged_text(0, amount)?, "¥748.00");
+
+    let elements = file.elements_for_object(1, 65);
+    assert_eq!(elements.len(), 1);
+    assert_eq!(elements[0].0, "Buyer/BuyerName");
+
+    assert!(ofd.page_object_by_id(0, 1, 9999)?.is_none());
+    assert!(ofd.page_object_by_id(0, 9999, 65).is_err());
+
+    let ofd = from_path("../samples/002.ofd")?;
+    assert!(ofd.custom_tag_files_for_doc(0)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_extensions() -> Result<()> {
+    let ofd = from_path("../samples/000.ofd")?;
+    assert!(ofd.extensions_for_doc(0)?.is_none());
+
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ofd:Extensions xmlns:ofd="http://www.ofdspec.org/2016"><ofd:Extension AppName="app" Company="corp" RefId="65" Date="2023-12-19T10:23:00"><ofd:Property Name="k" Type="string">v</ofd:Property><ofd:Data><v:Meta xmlns:v="urn:vendor" level="2"><v:Item>x</v:Item></v:Meta></ofd:Data><ofd:ExtendData>Ext/data.bin</ofd:ExtendData></ofd:Extension></ofd:Extensions>"#;
+    let mut editor = OfdEditor::new(ofd);
+    editor.document_mut(0)?.extensions = Some("Extensions.xml".into());
+    editor.put_bytes("Doc_0/Extensions.xml", xml);
+    editor.put_bytes("Doc_0/Ext/data.bin", b"data".to_vec());
+    let bytes = editor.save_to(Cursor::new(Vec::new()))?.into_inner();
+
+    let ofd = from_bytes(bytes)?;
+    let ext = ofd.extensions_for_doc(0)?.unwrap();
+    assert_eq!(ext.path().as_str(), "Doc_0/Extensions.xml");
+    let extension = &ext.extensions.as_ref().unwrap()[0];
+    assert_eq!(extension.app_name, "app");
+    assert_eq!(extension.ref_id, 65);
+    assert_eq!(extension.props.len(), 3);
+    match &extension.props[0] {
+        Prop::Property { name, value, .. } => {
+            assert_eq!((name.as_str(), value.as_str()), ("k", "v"))
+        }
+        p => panic!("unexpected prop {:?}", p),
+    }
+    let Prop::Data(data) = &extension.props[1] else {
+        panic!("unexpected prop {:?}", extension.props[1]);
+    };
+    let meta = data.get_child("Meta", "urn:vendor").unwrap();
+    assert_eq!(meta.attr("level"), Some("2"));
+    assert_eq!(meta.get_child("Item", "urn:vendor").unwrap().text(), "x");
+
+    let extend_data = ext.extend_data()?;
+    assert_eq!(extend_data[0].1.as_str(), "Doc_0/Ext/data.bin");
+    assert_eq!(ofd.bytes(extend_data[0].1.as_str())?, b"data");
+
+    // written back as it is
+    let mut editor = OfdEditor::new(ofd.clone());
+    editor.put_xml(ext.path().as_str(), (*ext.content).clone());
+    let bytes = editor.save_to(Cursor::new(Vec::new()))?.into_inner();
+    let saved = from_bytes(bytes)?.extensions_for_doc(0)?.unwrap();
+    let Prop::Data(saved_data) = &saved.extensions.as_ref().unwrap()[0].props[1] else {
+        panic!("data lost");
+    };
+    assert_eq!(saved_data, data);
+    Ok(())
+}
+
+#[test]
+fn test_versions() -> Result<()> {
+    let ofd = from_path("../samples/000.ofd")?;
+    assert!(ofd.versions_for_doc(0)?.is_empty());
+    assert!(ofd.current_version(0)?.is_none());
+
+    let version = |id: &str, index: i32, current: bool| Version {
+        id: id.into(),
+        index,
+        current: Some(current),
+        base_loc: format!("Doc_0/Versions/{id}/DocVersion.xml").into(),
+    };
+    let file = |id: &str, path: &str| File {
+        id: id.into(),
+        path: path.into(),
+    };
+    let mut editor = OfdEditor::new(ofd.clone());
+    editor.entry_mut()?.doc_body[0].versions = Some(Versions {
+        version: vec![version("v1", 1, true), version("v0", 0, false)],
+    });
+    editor.put_xml(
+        "Doc_0/Versions/v0/DocVersion.xml",
+        VersionXmlFile {
+            id: "v0".into(),
+            version: Some("0".into()),
+            name: None,
+            creation_date: None,
+            file_list: FileList {
+                files: vec![file("v0-doc", "/Doc_0/Document.xml")],
+            },
+            doc_root: "/Doc_0/".into(),
+        },
+    );
+    editor.put_xml(
+        "Doc_0/Versions/v1/DocVersion.xml",
+        VersionXmlFile {
+            id: "v1".into(),
+            version: Some("1".into()),
+            name: Some("edited".into()),
+            creation_date: None,
+            file_list: FileList {
+                files: vec![
+                    file("v1-doc", "Document.xml"),
+                    file("v1-page", "Page_0.xml"),
+                ],
+            },
+            doc_root: "Document.xml".into(),
+        },
+    );
+    let mut doc = Rc::unwrap_or_clone(ofd.document_by_index(0)?.content);
+    doc.pages.page[0].base_loc = "Page_0.xml".into();
+    editor.put_xml("Doc_0/Versions/v1/Document.xml", doc);
+    let mut page = Rc::unwrap_or_clone(ofd.page_by_index(0, 0)?.content);
+    page.area = None;
+    editor.put_xml("Doc_0/Versions/v1/Page_0.xml", page);
+    let bytes = editor.save_to(Cursor::new(Vec::new()))?.into_inner();
+
+    let ofd = from_bytes(bytes)?;
+    let versions = ofd.versions_for_doc(0)?;
+    let ids = versions
+        .iter()
+        .map(|(v, _)| v.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["v0", "v1"]);
+    let (current, file) = ofd.current_version(0)?.unwrap();
+    assert_eq!(current.id, "v1");
+    assert_eq!(file.name.as_deref(), Some("edited"));
+    let files = file.files()?;
+    assert_eq!(files[1], ("v1-page", "Doc_0/Versions/v1/Page_0.xml".into()));
+
+    assert!(ofd.page_by_index(0, 0)?.area.is_some());
+    ofd.select_version(0, Some("v1"))?;
+    let page = ofd.page_by_index(0, 0)?;
+    assert_eq!(page.path().as_str(), "Doc_0/Versions/v1/Page_0.xml");
+    assert!(page.area.is_none());
+
+    ofd.select_version(0, Some("v0"))?;
+    let doc = ofd.document_by_index(0)?;
+    assert_eq!(doc.path().as_str(), "Doc_0/Document.xml");
+    assert!(ofd.page_by_index(0, 0)?.area.is_some());
+
+    assert!(ofd.select_version(0, Some("v9")).is_err());
+    ofd.select_version(0, None)?;
+    assert_eq!(
+        ofd.document_by_index(0)?.path().as_str(),
+        "Doc_0/Document.xml"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_version_files() -> Result<()> {
+    let ofd = from_path("../samples/000.ofd")?;
+    let page = ofd.page_by_index(0, 0)?;
+    let content = page.path().strip_prefix("Doc_0").unwrap().to_owned();
+    let mut editor = OfdEditor::new(ofd.clone());
+    editor.entry_mut()?.doc_body[0].versions = Some(Versions {
+        version: vec![Version {
+            id: "v1".into(),
+            index: 1,
+            current: Some(true),
+            base_loc: "Doc_0/Versions/v1/DocVersion.xml".into(),
+        }],
+    });
+    editor.put_xml(
+        "Doc_0/Versions/v1/DocVersion.xml",
+        VersionXmlFile {
+            id: "v1".into(),
+            version: None,
+            name: None,
+            creation_date: None,
+            file_list: FileList {
+                files: vec![
+                    File {
+                        id: "v1-doc".into(),
+                        path: "/Doc_0/Document.xml".into(),
+                    },
+                    File {
+                        id: "v1-page".into(),
+                        path: content.as_str().into(),
+                    },
+                ],
+            },
+            doc_root: "/Doc_0/Document.xml".into(),
+        },
+    );
+    let mut edited = Rc::unwrap_or_clone(page.content.clone());
+    edited.area = None;
+    editor.put_xml(format!("Doc_0/Versions/v1/{content}"), edited);
+    let bytes = editor.save_to(Cursor::new(Vec::new()))?.into_inner();
+
+    let ofd = from_bytes(bytes)?;
+    let original = ofd.bytes(page.path().as_str())?;
+    ofd.select_version(0, Some("v1"))?;
+    let page = ofd.page_by_index(0, 0)?;
+    assert_eq!(page.path().as_str(), format!("Doc_0/{content}"));
+    assert!(page.area.is_none());
+    assert_ne!(ofd.bytes(page.path().as_str())?, original);
+
+    ofd.select_version(0, None)?;
+    assert!(ofd.page_by_index(0, 0)?.area.is_some());
+    assert_eq!(ofd.bytes(page.path().as_str())?, original);
+    Ok(())
+}
+
+#[test]
+fn test_id_index() -> Result<()> {
+    let ofd = from_path("../samples/000.ofd")?;
+    let index = ofd.id_index(0)?;
+    let page_id = ofd.document_by_index(0)?.pages.page[0].id;
+    assert_eq!(ofd.page_index_by_id(0, page_id)?, Some(0));
+    assert_eq!(ofd.page_index_by_id(0, 65)?, None);
+    assert!(matches!(
+        ofd.object_by_id(0, 65)?,
+        Some(VtGraphicUnit::TextObject(t)) if t.id == 65
+    ));
+    assert!(ofd.object_by_id(0, page_id)?.is_none());
+    assert!(matches!(
+        index.get(4),
+        Some(IdTarget::Resource {
+            kind: ResourceKind::DrawParam,
+            ..
+        })
+    ));
+    let tpl = RelativePathBuf::from("Doc_0/Tpls/Tpl_0/Content.xml");
+    assert_eq!(index.get(3), Some(&IdTarget::Layer { path: tpl }));
+    assert!(index.duplicates().is_empty());
+    assert!(index.exceeding_max_unit_id().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_outlines() -> Result<()> {
+    let ofd = from_path("../samples/000.ofd")?;
+    assert!(ofd.outlines_for_doc(0)?.is_empty());
+    let page_id = ofd.document_by_index(0)?.pages.page[0].id;
+
+    let dest = CtDest {
+        r#type: "XYZ".into(),
+        page_id,
+        left: Some(10.0),
+        right: None,
+        top: Some(20.0),
+        bottom: None,
+        zoom: Some(1.5),
+    };
+    let action = |action_type| Actions {
+        actions: vec![CtAction {
+            event: Event::Click,
+            region: None,
+            action_type,
+        }],
+    };
+    let elem = |title: &str, action_type, children| CtOutlineElem {
+        title: title.into(),
+        count: None,
+        expanded: None,
+        actions: Some(action(action_type)),
+        outline_elems: children,
+    };
+    let goto = |value| ActionType::Goto { value };
+    let outlines = vec![
+        elem(
+            "chapter",
+            goto(VtTo::Dest(dest.clone())),
+            Some(vec![
+                elem("mark", goto(VtTo::Bookmark { name: "m1".into() }), None),
+                elem(
+                    "site",
+                    ActionType::Uri {
+                        uri: "https://example.com".into(),
+                        base: None,
+                    },
+                    None,
+                ),
+            ]),
+        ),
+        elem(
+            "missing",
+            goto(VtTo::Dest(CtDest {
+                page_id: 9999,
+                ..dest.clone()
+            })),
+            None,
+        ),
+    ];
+    let mut editor = OfdEditor::new(ofd);
+    let doc = editor.document_mut(0)?;
+    doc.outlines = Some(Outlines {
+        outline_elems: outlines,
+    });
+    doc.bookmarks = Some(Bookmarks {
+        bookmarks: vec![CtBookmark {
+            name: "m1".into(),
+            dest: dest.clone(),
+        }],
+    });
+    let ofd = from_bytes(editor.save_to(Cursor::new(Vec::new()))?.into_inner())?;
+
+    let entries = ofd.outlines_for_doc(0)?;
+    let titles = entries
+        .iter()
+        .map(|e| (e.title.as_str(), e.depth))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        titles,
+        [("chapter", 0), ("mark", 1), ("site", 1), ("missing", 0)]
+    );
+    for entry in &entries[..2] {
+        let Some(NavTarget::Page { page_index, dest }) = &entry.target else {
+            panic!("not a page target");
+        };
+        assert_eq!(*page_index, 0);
+        assert_eq!(dest.r#type, "XYZ");
+        assert_eq!(dest.top, Some(20.0));
+        assert_eq!(dest.zoom, Some(1.5));
+    }
+    assert!(matches!(
+        &entries[2].target,
+        Some(NavTarget::Uri { uri, .. }) if uri == "https://example.com"
+    ));
+    assert!(entries[3].target.is_none());
+
+    let bookmarks = ofd.bookmarks_for_doc(0)?;
+    assert_eq!(bookmarks[0].name, "m1");
+    assert_eq!(bookmarks[0].page_index, Some(0));
+    Ok(())
+}
+
+#[test]
+fn test_validate() -> Result<()> {
+    let ofd = from_path("../samples/000.ofd")?;
+    assert_eq!(ofd.validate()?, vec![]);
+
+    // the templates refer to draw params never defined
+    let issues = from_path("../samples/001.ofd")?.validate()?;
+    assert!(issues
+        .iter()
+        .any(|i| i.path == "Doc_0/Tpls/Tpl_0/Content.xml"
+            && i.kind
+                == IssueKind::MissingId {
+                    name: "DrawParam",
+                    id: 4
+                }));
+
+    let mut editor = OfdEditor::new(ofd);
+    editor.remove("Doc_0/Res/image_78.jb2");
+    let page = editor.page_mut(0, 0)?;
+    page.template.as_mut().unwrap()[0].template_id = 999;
+    let objects = page.content.as_mut().unwrap().layer[0]
+        .objects
+        .as_mut()
+        .unwrap();
+    let VtGraphicUnit::TextObject(text) = &mut objects[0] else {
+        panic!("not a text object");
+    };
+    text.boundary = (0.0, 0.0, -1.0, 5.0).into();
+    text.ctm = Some(vec![1.0, 0.0].into());
+    text.font = 999;
+    let VtGraphicUnit::TextObject(text) = &mut objects[1] else {
+        panic!("not a text object");
+    };
+    text.id = 62;
+    let res = editor.xml_mut::<ResourceXmlFile>("Doc_0/DocumentRes.xml")?;
+    let Some(Resource::DrawParams(dps)) = res.resources.as_mut().unwrap().first_mut() else {
+        panic!("not draw params");
+    };
+    dps.draw_params[0].fill_color.as_mut().unwrap().color_space = Some(998);
+    let ofd = from_bytes(editor.save_to(Cursor::new(Vec::new()))?.into_inner())?;
+
+    let page = RelativePathBuf::from("Doc_0/Pages/Page_0/Content.xml");
+    let issues = ofd.validate()?;
+    let kinds = |path: &str| {
+        issues
+            .iter()
+            .filter(|i| i.path == path)
+            .map(|i| i.kind.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        kinds("Doc_0/DocumentRes.xml"),
+        [
+            IssueKind::MissingId {
+                name: "ColorSpace",
+                id: 998
+            },
+            IssueKind::MissingItem {
+                name: "MediaFile",
+                target: "Doc_0/Res/image_78.jb2".into()
+            }
+        ]
+    );
+    assert_eq!(
+        kinds(page.as_str()),
+        [
+            IssueKind::DuplicateId {
+                id: 62,
+                first: IdTarget::Object { path: page.clone() }
+            },
+            IssueKind::MissingId {
+                name: "TemplateID",
+                id: 999
+            },
+            IssueKind::NegativeBoundary {
+                id: 62,
+                boundary: (0.0, 0.0, -1.0, 5.0).into()
+            },
+            IssueKind::InvalidCtm { id: 62, len: 2 },
+            IssueKind::MissingId {
+                name: "Font",
+                id: 999
+            },
+        ]
+    );
+    // the removed image is signed too
+    assert_eq!(
+        kinds("Doc_0/Signs/Sign_0/Signature.xml"),
+        [IssueKind::MissingItem {
+            name: "FileRef",
+            target: "Doc_0/Res/image_78.jb2".into()
+        }]
+    );
+    assert_eq!(issues.len(), 8);
+    Ok(())
+}
+
+#[test]
+fn test_validate_schema() -> Result<()> {
+    let ofd = from_path("../samples/000.ofd")?;
+    let violations = ofd.validate_schema()?;
+    let found = |path: &str, element: &str, message: &str| {
+        violations
+            .iter()
+            .any(|v| v.path == path && v.element == element && v.message == message)
+    };
+    // `Template` must come before `Area`
+    assert!(found(
+        "Doc_0/Pages/Page_0/Content.xml",
+        "/Page",
+        r#"unexpected element "Template""#
+    ));
+    assert!(found(
+        "Doc_0/Tags/CustomTags.xml",
+        "/CustomTags/CustomTag[1]",
+        r#"missing required attribute "NameSpace""#
+    ));
+    assert!(!violations
+        .iter()
+        .any(|v| v.path == "Doc_0/DocumentRes.xml" || v.path == "Doc_0/PublicRes.xml"));
+    Ok(())
+}
+
+#[test]
+fn test_lenient() -> Result<()> {
+    let ofd = from_path("../samples/000.ofd")?;
+    let objects = ofd
+        .page_by_index(0, 0)?
+        .content
+        .content
+        .as_ref()
+        .unwrap()
+        .layer[0]
+        .objects
+        .as_ref()
+        .unwrap()
+        .len();
+    let doc = String::from_utf8(ofd.bytes("Doc_0/Document.xml")?)?;
+    let page = String::from_utf8(ofd.bytes("Doc_0/Pages/Page_0/Content.xml")?)?;
+
+    let mut editor = OfdEditor::new(ofd);
+    editor.entry_mut()?.doc_body[0].doc_root = None;
+    editor.put_bytes("Doc_0/Document.xml", doc.replace("0 0 0 0", "0 0 x"));
+    editor.put_bytes(
+        "Doc_0/Pages/Page_0/Content.xml",
+        page.replace("69 7 72 7.6749", "69 7 bad"),
+    );
+    editor.remove("Doc_0/PublicRes.xml");
+    let bytes = editor.save_to(Cursor::new(Vec::new()))?.into_inner();
+
+    let ofd = from_bytes(&bytes)?;
+    assert!(ofd.document_by_index(0).is_err());
+
+    ofd.set_lenient(true);
+    let doc = ofd.document_by_index(0)?;
+    assert_eq!(doc.path(), "Doc_0/Document.xml");
+    assert_eq!(
+        doc.common_data.page_area.physical_box,
+        (0.0, 0.0, 210.0, 297.0).into()
+    );
+    let page = ofd.page_by_index(0, 0)?;
+    let layer = &page.content.content.as_ref().unwrap().layer[0];
+    assert_eq!(layer.objects.as_ref().unwrap().len(), objects - 1);
+    assert!(ofd.resources_for_page(0, 0).is_ok());
+
+    let recoveries = ofd.repair()?;
+    let kinds = |path: &str| {
+        recoveries
+            .iter()
+            .filter(|r| r.path == path)
+            .map(|r| r.kind.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        kinds("Doc_0/Document.xml"),
+        [
+            RecoveryKind::DocRootLocated { doc_index: 0 },
+            RecoveryKind::DefaultPageArea
+        ]
+    );
+    assert_eq!(kinds("Doc_0/PublicRes.xml"), [RecoveryKind::MissingItem]);
+    let skipped = kinds("Doc_0/Pages/Page_0/Content.xml");
+    assert!(matches!(
+        &skipped[..],
+        [RecoveryKind::SkippedElement { element, .. }] if element == "TextObject"
+    ));
+
+    // the repaired package reads without lenient mode
+    let repaired = from_bytes(ofd.save_repaired_to(Cursor::new(Vec::new()))?.into_inner())?;
+    let doc = repaired.document_by_index(0)?;
+    assert_eq!(
+        doc.common_data.page_area.physical_box,
+        (0.0, 0.0, 210.0, 297.0).into()
+    );
+    let page = repaired.page_by_index(0, 0)?;
+    let layer = &page.content.content.as_ref().unwrap().layer[0];
+    assert_eq!(layer.objects.as_ref().unwrap().len(), objects - 1);
+    assert!(repaired.contains("Doc_0/PublicRes.xml"));
+    assert!(repaired.resources_for_page(0, 0).is_ok());
+    assert!(repaired.recoveries().is_empty());
+
+    // repairing leaves the reading mode as it was
+    assert!(ofd.is_lenient());
+    let strict = from_path("../samples/000.ofd")?;
+    assert!(strict.repair()?.is_empty());
+    assert!(!strict.is_lenient());
+    Ok(())
+}
+
+#[test]
+fn test_error_location() -> Result<()> {
+    let ofd = from_path("../samples/000.ofd")?;
+    let page = String::from_utf8(ofd.bytes("Doc_0/Pages/Page_0/Content.xml")?)?;
+    let mut editor = OfdEditor::new(ofd);
+    editor.put_bytes(
+        "Doc_0/Pages/Page_0/Content.xml",
+        page.replace("164 6.2 40 5", "164 6.2 40"),
+    );
+    let ofd = from_bytes(editor.save_to(Cursor::new(Vec::new()))?.into_inner())?;
+
+    let err = ofd.page_by_index(0, 0).unwrap_err();
+    assert_eq!(err.item_path(), Some("Doc_0/Pages/Page_0/Content.xml"));
+    assert_eq!(
+        err.element_path(),
+        Some("/Page/Content/Layer[@ID=60]/TextObject[@ID=64]")
+    );
+    let Error::ItemDeError { source, .. } = &err else {
+        panic!("not a deserialization error");
+    };
+    assert_eq!(source.attr(), Some("Boundary"));
+    Ok(())
+}
+
+#[test]
+fn test_from_reader() -> Result<()> {
+    let file = std::fs::File::open("../samples/000.ofd")?;
+    let ofd = from_reader(file)?;
+    assert_eq!(
+        ofd.page_by_index(0, 0)?.path(),
+        "Doc_0/Pages/Page_0/Content.xml"
+    );
+
+    let ofd = unsafe { from_path_mmap("../samples/000.ofd")? };
+    assert_eq!(ofd.document_by_index(0)?.pages.page.len(), 1);
+
+    // the entry is checked the same way
+    let mut editor = OfdEditor::new(ofd);
+    editor.remove("OFD.xml");
+    let bytes = editor.save_to(Cursor::new(Vec::new()))?.into_inner();
+    assert!(matches!(
+        from_reader(Cursor::new(bytes.clone())),
+        Err(Error::OfdEntryNotFound)
+    ));
+    assert!(matches!(
+        SharedOfd::from_bytes(bytes),
+        Err(Error::OfdEntryNotFound)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_limits() -> Result<()> {
+    let ofd = from_path("../samples/000.ofd")?;
+    ofd.set_limits(Limits::untrusted())?;
+    assert_eq!(
+        ofd.page_by_index(0, 0)?.path(),
+        "Doc_0/Pages/Page_0/Content.xml"
+    );
+
+    let count = ofd.item_names().len();
+    let limits = Limits {
+        max_items: Some(count - 1),
+        ..Default::default()
+    };
+    assert!(matches!(
+        ofd.set_limits(limits),
+        Err(Error::TooManyItems { max, .. }) if max == count - 1
+    ));
+
+    let path = "Doc_0/Signs/Sign_0/SignedValue.dat";
+    let ofd = from_path("../samples/000.ofd")?;
+    ofd.set_limits(Limits {
+        max_item_size: Some(16),
+        ..Default::default()
+    })?;
+    assert!(matches!(
+        ofd.bytes(path),
+        Err(Error::ItemTooLarge { max: 16, .. })
+    ));
+    assert!(ofd.copy_item_to(path, &mut std::io::sink()).is_err());
+
+    let ofd = from_path("../samples/000.ofd")?;
+    ofd.set_limits(Limits {
+        max_compression_ratio: Some(1),
+        ..Default::default()
+    })?;
+    assert!(matches!(
+        ofd.entry(),
+        Err(Error::CompressionRatioExceeded { max: 1, .. })
+    ));
+
+    let ofd = from_path("../samples/000.ofd")?;
+    ofd.set_limits(Limits {
+        max_xml_depth: Some(2),
+        ..Default::default()
+    })?;
+    assert!(matches!(
+        ofd.document_by_index(0),
+        Err(Error::XmlTooDeep { max: 2, .. })
+    ));
+
+    let size = from_path("../samples/000.ofd")?.bytes(path)?.len() as u64;
+    let ofd = from_path("../samples/000.ofd")?;
+    ofd.set_limits(Limits {
+        max_total_size: Some(size + 1),
+        ..Default::default()
+    })?;
+    ofd.bytes(path)?;
+    assert!(matches!(
+        ofd.bytes(path),
+        Err(Error::TotalSizeExceeded { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_path_escapes_root() -> Result<()> {
+    let ofd = from_path("../samples/000.ofd")?;
+    let mut editor = OfdEditor::new(ofd);
+    editor.entry_mut()?.doc_body[0].doc_root = Some("../Doc_0/Document.xml".into());
+    let bytes = editor.save_to(Cursor::new(Vec::new()))?.into_inner();
+    let ofd = from_bytes(bytes)?;
+    assert!(matches!(
+        ofd.document_by_index(0),
+        Err(Error::PathEscapesRoot(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_cache_budget() -> Result<()> {
+    let ofd = from_path("../samples/000.ofd")?;
+    ofd.resources_for_page(0, 0)?;
+    let size = ofd.cache_size();
+    assert!(size > 0);
+    // shared resources are read once
+    ofd.resources_for_page(0, 0)?;
+    assert_eq!(ofd.cache_size(), size);
+
+    ofd.set_cache_budget(size / 2);
+    assert!(ofd.cache_size() <= size / 2);
+    ofd.resources_for_page(0, 0)?;
+    assert!(ofd.cache_size() <= size / 2);
+
+    ofd.set_cache_budget(0);
+    assert_eq!(ofd.cache_size(), 0);
+    assert_eq!(
+        ofd.page_by_index(0, 0)?.path(),
+        "Doc_0/Pages/Page_0/Content.xml"
+    );
+    assert_eq!(ofd.cache_size(), 0);
+
+    ofd.set_cache_budget(usize::MAX);
+    let page = ofd.page_by_index(0, 0)?;
+    assert!(ofd.cache_size() > 0);
+    // a cache hit shares the parsed item
+    assert!(Rc::ptr_eq(&page.content, &ofd.page_by_index(0, 0)?.content));
+    ofd.clear_cache();
+    assert_eq!(ofd.cache_size(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_extract_pages() -> Result<()> {
+    let ofd = from_path("../samples/001.ofd")?;
+    let pages = parse_page_ranges("4,2", 5)?;
+    let out = ofd.extract_pages(0, &pages, Cursor::new(Vec::new()))?;
+    let extracted = from_bytes(out.into_inner())?;
+
+    let doc = extracted.document_by_index(0)?;
+    let ids = doc.pages.page.iter().map(|p| p.id).collect::<Vec<_>>();
+    assert_eq!(ids, [450, 92]);
+    let tpls = doc.common_data.template_page.as_ref().unwrap();
+    let tpl_ids = tpls.iter().map(|t| t.id).collect::<Vec<_>>();
+    assert_eq!(tpl_ids, [91, 449]);
+    assert!(doc.annotations.is_none());
+    assert!(doc.attachments.is_none());
+    assert!(extracted.entry()?.doc_body[0].signatures.is_none());
+
+    // the image and the annotation of the first page are dropped with it
+    let names = extracted.item_names();
+    for dropped in [
+        "Doc_0/Res/qrcode.png",
+        "Doc_0/Pages/Page_0/Content.xml",
+        "Doc_0/Tpls/Tpl_0/Content.xml",
+        "Doc_0/Annots/Page_0/Annotation.xml",
+        "Doc_0/Signs/Sign_0/Signature.xml",
+    ] {
+        assert!(!names.iter().any(|n| n == dropped), "{dropped}");
+    }
+    let res = extracted.resources_for_page(0, 0)?;
+    assert_eq!(res.iter().count(), 2);
+    assert_eq!(
+        extracted.bytes("Doc_0/Pages/Page_3/Content.xml")?,
+        ofd.bytes("Doc_0/Pages/Page_3/Content.xml")?
+    );
+    // nothing new, the templates refer to draw params never defined
+    let issues = extracted.validate()?;
+    assert!(issues.iter().all(|i| matches!(
+        i.kind,
+        IssueKind::MissingId {
+            name: "DrawParam",
+            ..
+        }
+    )));
+
+    let annotated = ofd.extract_pages(0, &[0], Cursor::new(Vec::new()))?;
+    let annotated = from_bytes(annotated.into_inner())?;
+    assert_eq!(annotated.annotations_for_page(0, 0)?.len(), 1);
+    assert!(annotated.bytes("Doc_0/Res/qrcode.png").is_ok());
+
+    let res = ofd.extract_pages(0, &[5], Cursor::new(Vec::new()));
+    assert!(matches!(res, Err(Error::NoSuchPage)));
+    Ok(())
+}
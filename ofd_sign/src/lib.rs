@@ -80,7 +80,7 @@ mod tests {
         let sig_vec = f.signature_for_doc(0)?.unwrap();
         let sig0 = &sig_vec[0];
 
-        let path = sig0.resolve(&sig0.signed_value);
+        let path = sig0.resolve(&sig0.signed_value)?;
         dbg!(&path);
         let _sign = f.bytes(path)?;
         // File::create("../samples/SignedValue.dat")?.write_all(&_sign)?;
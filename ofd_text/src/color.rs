@@ -0,0 +1,39 @@
+use ofd_base::common::CtColor;
+use ofd_base::file::res::{ColorSpace, Type, SRGB};
+use ofd_rw::Resources;
+
+pub(crate) const BLACK: &str = "#000000";
+
+/// a plain color as `#rrggbb`, `None` for patterns, shadings and invalid colors
+pub(crate) fn to_hex(color: &CtColor, resources: &Resources) -> Option<String> {
+    let cs = color_space(color, resources);
+    let value = match (&color.value, color.index) {
+        (Some(value), _) => value,
+        (None, Some(index)) => cs.palette.as_ref()?.cv.get(index)?,
+        _ => return None,
+    };
+    if value.len() != cs.r#type.channel_count() {
+        return None;
+    }
+    let max = ((1_u32 << cs.bits_per_component.unwrap_or(8).min(16)) - 1) as f32;
+    let v = |i: usize| value[i] as f32 / max;
+    let (r, g, b) = match cs.r#type {
+        Type::RGB => (v(0), v(1), v(2)),
+        Type::GRAY => (v(0), v(0), v(0)),
+        Type::CMYK => {
+            let k = 1.0 - v(3);
+            ((1.0 - v(0)) * k, (1.0 - v(1)) * k, (1.0 - v(2)) * k)
+        }
+    };
+    let c = |x: f32| (x.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Some(format!("#{:02x}{:02x}{:02x}", c(r), c(g), c(b)))
+}
+
+/// the color space of a color, the default one of the document, or sRGB
+fn color_space<'a>(color: &CtColor, resources: &'a Resources) -> &'a ColorSpace {
+    color
+        .color_space
+        .or(resources.default_cs)
+        .and_then(|id| resources.get_color_space_by_id(id))
+        .unwrap_or(&SRGB)
+}
@@ -0,0 +1,15 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    OfdError(#[from] ofd_rw::error::Error),
+
+    #[error(r#"invalid DeltaX or DeltaY: "{0}""#)]
+    InvalidDelta(String),
+
+    #[error("invalid search query: {0}")]
+    InvalidQuery(#[from] regex::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
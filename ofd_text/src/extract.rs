@@ -0,0 +1,205 @@
+use crate::error::Result;
+use crate::font::FontInfo;
+use crate::glyph::{page_glyphs, Glyph};
+use crate::layout::{layout, PageText};
+use crate::positioned::{PositionedChar, PositionedPage};
+use crate::search::{find, pattern, SearchHit, SearchOptions};
+use ofd_base::file::page::PageXmlFile;
+use ofd_base::StRefId;
+use ofd_rw::{Ofd, Resources, Storage};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+use tracing::warn;
+
+/// extract the text of pages, from the text objects on the pages,
+/// their templates and annotations
+pub struct TextExtractor<S> {
+    ofd: Ofd<S>,
+    /// fonts by doc index and font id
+    fonts: HashMap<(usize, StRefId), Rc<FontInfo>>,
+}
+
+impl<S: Storage> TextExtractor<S> {
+    pub fn new(ofd: Ofd<S>) -> Self {
+        Self {
+            ofd,
+            fonts: HashMap::new(),
+        }
+    }
+
+    pub fn page_text(&mut self, doc_index: usize, page_index: usize) -> Result<PageText> {
+        let glyphs = self.glyphs(doc_index, page_index)?;
+        Ok(PageText {
+            doc_index,
+            page_index,
+            paragraphs: layout(glyphs),
+        })
+    }
+
+    /// all the pages of a document
+    pub fn document_text(&mut self, doc_index: usize) -> Result<Vec<PageText>> {
+        let doc = self.ofd.document_by_index(doc_index)?;
+        (0..doc.pages.page.len())
+            .map(|page_index| self.page_text(doc_index, page_index))
+            .collect()
+    }
+
+    /// text of all the documents, pages are separated by a form feed
+    pub fn text(&mut self) -> Result<String> {
+        let entry = self.ofd.entry()?;
+        let mut pages = vec![];
+        for doc_index in 0..entry.doc_body.len() {
+            for page in self.document_text(doc_index)? {
+                pages.push(page.text());
+            }
+        }
+        Ok(pages.join("\n\x0c"))
+    }
+
+    /// each character of a page with its box, font and color
+    pub fn positioned_text(
+        &mut self,
+        doc_index: usize,
+        page_index: usize,
+    ) -> Result<PositionedPage> {
+        let doc = self.ofd.document_by_index(doc_index)?;
+        let page = self.ofd.page_by_index(doc_index, page_index)?;
+        let area = page
+            .area
+            .as_ref()
+            .unwrap_or(&doc.common_data.page_area)
+            .physical_box;
+        let chars = self
+            .glyphs(doc_index, page_index)?
+            .into_iter()
+            .map(|g| PositionedChar::new(page_index, g))
+            .collect();
+        Ok(PositionedPage {
+            doc_index,
+            page_index,
+            width: area.w,
+            height: area.h,
+            chars,
+        })
+    }
+
+    /// find text in all the documents and pages, see [SearchOptions]
+    pub fn search(&mut self, query: &str, options: &SearchOptions) -> Result<Vec<SearchHit>> {
+        let Some(pattern) = pattern(query, options)? else {
+            return Ok(vec![]);
+        };
+        let entry = self.ofd.entry()?;
+        let mut hits = vec![];
+        for doc_index in 0..entry.doc_body.len() {
+            let doc = self.ofd.document_by_index(doc_index)?;
+            for page_index in 0..doc.pages.page.len() {
+                let glyphs = self.glyphs(doc_index, page_index)?;
+                hits.extend(find(&pattern, glyphs, doc_index, page_index));
+            }
+        }
+        Ok(hits)
+    }
+
+    fn glyphs(&mut self, doc_index: usize, page_index: usize) -> Result<Vec<Glyph>> {
+        let page = self.ofd.page_by_index(doc_index, page_index)?;
+        let templates = self.ofd.templates_for_page(doc_index, page_index)?;
+        let templates = templates
+            .iter()
+            .map(|t| &*t.content)
+            .collect::<Vec<&PageXmlFile>>();
+        let annotations = self.ofd.annotations_for_page(doc_index, page_index)?;
+        let annotations = annotations.iter().map(|a| &*a.content).collect::<Vec<_>>();
+        let resources = self.ofd.resources_for_page(doc_index, page_index)?;
+        page_glyphs(
+            &templates,
+            &page.content,
+            &annotations,
+            &resources,
+            |font_id| self.font(doc_index, &resources, font_id),
+        )
+    }
+
+    /// a font resource with its embedded font program
+    fn font(&mut self, doc_index: usize, resources: &Resources, font_id: StRefId) -> Rc<FontInfo> {
+        let ofd = &self.ofd;
+        let font = self.fonts.entry((doc_index, font_id)).or_insert_with(|| {
+            let Some((file, font)) = resources.get_font_by_id(font_id) else {
+                warn!("font id = {font_id} is not defined!");
+                return Rc::default();
+            };
+            let data = font.font_file.as_ref().and_then(|font_file| {
+                let path = PathBuf::from(&file.content.base_loc).join(font_file);
+                let bytes = file.resolve(&path).map(|p| ofd.bytes(p.as_str()));
+                match bytes {
+                    Ok(Ok(bytes)) => Some(bytes),
+                    Ok(Err(e)) | Err(e) => {
+                        warn!("can not read font file {}: {e}", font_file.display());
+                        None
+                    }
+                }
+            });
+            Rc::new(FontInfo::new(font.font_name.clone(), data))
+        });
+        font.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::Result;
+    use ofd_rw::from_path;
+
+    #[test]
+    fn test_page_text() -> Result<()> {
+        let ofd = from_path("../samples/000.ofd")?;
+        let mut extractor = TextExtractor::new(ofd);
+        let page = extractor.page_text(0, 0)?;
+        let text = page.text();
+        assert!(text.contains("广东增值税电子普通发票"));
+        assert!(text.contains("沈阳辽广天地传媒广告有限公司"));
+        assert!(text.contains("*住宿服务*住宿费"));
+        // from the template
+        assert!(text.contains("购"));
+
+        let ofd = from_path("../samples/001.ofd")?;
+        let mut extractor = TextExtractor::new(ofd);
+        assert_eq!(extractor.document_text(0)?.len(), 5);
+        assert_eq!(extractor.text()?.matches('\x0c').count(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_positioned_text() -> Result<()> {
+        let ofd = from_path("../samples/000.ofd")?;
+        let mut extractor = TextExtractor::new(ofd);
+        let page = extractor.positioned_text(0, 0)?;
+        assert_eq!((page.width, page.height), (210.0, 140.0));
+
+        let title = page
+            .chars
+            .iter()
+            .skip_while(|c| c.text != "广")
+            .take(11)
+            .collect::<Vec<_>>();
+        let text = title.iter().map(|c| c.text.as_str()).collect::<String>();
+        assert_eq!(text, "广东增值税电子普通发票");
+        // on one line, left to right, inside the page
+        for pair in title.windows(2) {
+            assert_eq!(pair[0].object_id, pair[1].object_id);
+            assert!(pair[0].bbox.x < pair[1].bbox.x);
+            assert!((pair[0].bbox.y - pair[1].bbox.y).abs() < 0.01);
+        }
+        let first = title[0];
+        assert!(first.bbox.x > 0.0 && first.bbox.x + first.bbox.w < page.width);
+        assert!(first.bbox.h > first.font_size * 0.9);
+        assert_eq!(first.font_name, "KaiTi");
+
+        let json = serde_json::to_value(&page)?;
+        assert_eq!(json["chars"][0]["page_index"], 0);
+        assert!(json["chars"][0]["bbox"]["w"].is_number());
+        assert!(json["chars"][0]["color"].as_str().unwrap().starts_with('#'));
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashMap;
+use ttf_parser::cmap::Subtable;
+use ttf_parser::{Face, GlyphId, PlatformId};
+
+/// ascent and descent of fonts without an embedded font program, in ems
+const DEFAULT_ASCENT: f32 = 0.88;
+const DEFAULT_DESCENT: f32 = 0.12;
+
+/// windows symbol fonts map `0x20..0xff` to `0xf020..0xf0ff`
+const SYMBOL_OFFSET: u32 = 0xf000;
+
+/// glyph names of the adobe glyph list that are not a single char
+const GLYPH_NAMES: &[(&str, char)] = &[
+    ("space", ' '),
+    ("exclam", '!'),
+    ("quotedbl", '"'),
+    ("numbersign", '#'),
+    ("dollar", '$'),
+    ("percent", '%'),
+    ("ampersand", '&'),
+    ("quotesingle", '\''),
+    ("parenleft", '('),
+    ("parenright", ')'),
+    ("asterisk", '*'),
+    ("plus", '+'),
+    ("comma", ','),
+    ("hyphen", '-'),
+    ("period", '.'),
+    ("slash", '/'),
+    ("zero", '0'),
+    ("one", '1'),
+    ("two", '2'),
+    ("three", '3'),
+    ("four", '4'),
+    ("five", '5'),
+    ("six", '6'),
+    ("seven", '7'),
+    ("eight", '8'),
+    ("nine", '9'),
+    ("colon", ':'),
+    ("semicolon", ';'),
+    ("less", '<'),
+    ("equal", '='),
+    ("greater", '>'),
+    ("question", '?'),
+    ("at", '@'),
+    ("bracketleft", '['),
+    ("backslash", '\\'),
+    ("bracketright", ']'),
+    ("asciicircum", '^'),
+    ("underscore", '_'),
+    ("grave", '`'),
+    ("braceleft", '{'),
+    ("bar", '|'),
+    ("braceright", '}'),
+    ("asciitilde", '~'),
+    ("quoteleft", '\u{2018}'),
+    ("quoteright", '\u{2019}'),
+    ("quotedblleft", '\u{201c}'),
+    ("quotedblright", '\u{201d}'),
+    ("endash", '\u{2013}'),
+    ("emdash", '\u{2014}'),
+    ("bullet", '\u{2022}'),
+    ("ellipsis", '\u{2026}'),
+];
+
+/// a font resource, with the embedded font program if any
+#[derive(Debug, Default)]
+pub(crate) struct FontInfo {
+    pub name: String,
+    data: Option<Vec<u8>>,
+    /// glyphs to chars from the font program, built on first use
+    to_unicode: OnceCell<HashMap<u16, char>>,
+    /// glyphs to chars seen in text codes with both
+    learned: RefCell<HashMap<u16, char>>,
+}
+
+impl FontInfo {
+    /// a font without a usable font program is kept by name only
+    pub(crate) fn new(name: String, data: Option<Vec<u8>>) -> Self {
+        let data = data.filter(|d| Face::parse(d, 0).is_ok());
+        Self {
+            name,
+            data,
+            ..Default::default()
+        }
+    }
+
+    /// the char a glyph stands for
+    ///
+    /// tries the `cmap` of the font program, then the glyph names,
+    /// then what other text codes using this font told us.
+    pub(crate) fn to_unicode(&self, glyph: u16) -> Option<char> {
+        let to_unicode = self.to_unicode.get_or_init(|| {
+            let face = self.data.as_deref().and_then(|d| Face::parse(d, 0).ok());
+            face.as_ref().map(reverse_cmap).unwrap_or_default()
+        });
+        to_unicode
+            .get(&glyph)
+            .copied()
+            .or_else(|| self.learned.borrow().get(&glyph).copied())
+    }
+
+    /// remember a glyph drawn for a char
+    pub(crate) fn learn(&self, glyph: u16, ch: char) {
+        if is_private_use(ch) {
+            return;
+        }
+        self.learned.borrow_mut().entry(glyph).or_insert(ch);
+    }
+
+    pub(crate) fn metrics(&self) -> Metrics<'_> {
+        Metrics {
+            face: self.data.as_deref().and_then(|d| Face::parse(d, 0).ok()),
+        }
+    }
+}
+
+/// metrics of a font in ems, estimated without a font program
+pub(crate) struct Metrics<'a> {
+    face: Option<Face<'a>>,
+}
+
+impl Metrics<'_> {
+    pub(crate) fn ascent(&self) -> f32 {
+        match &self.face {
+            Some(face) => face.ascender() as f32 / face.units_per_em() as f32,
+            None => DEFAULT_ASCENT,
+        }
+    }
+
+    /// positive, below the baseline
+    pub(crate) fn descent(&self) -> f32 {
+        match &self.face {
+            Some(face) => -(face.descender() as f32) / face.units_per_em() as f32,
+            None => DEFAULT_DESCENT,
+        }
+    }
+
+    /// advance of a glyph, or of the glyph mapped from `ch` by the font
+    pub(crate) fn advance(&self, ch: char, glyph: Option<u16>) -> Option<f32> {
+        let face = self.face.as_ref()?;
+        let glyph = glyph.map(GlyphId).or_else(|| face.glyph_index(ch))?;
+        let advance = face.glyph_hor_advance(glyph)?;
+        Some(advance as f32 / face.units_per_em() as f32)
+    }
+}
+
+/// glyphs to chars, from the unicode and symbol subtables of `cmap`
+/// and the glyph names of `post` or `CFF`
+fn reverse_cmap(face: &Face) -> HashMap<u16, char> {
+    let mut res = HashMap::new();
+    let subtables = face.tables().cmap.iter().flat_map(|c| c.subtables);
+    for subtable in subtables {
+        let symbol = is_symbol(&subtable);
+        if !subtable.is_unicode() && !symbol {
+            continue;
+        }
+        subtable.codepoints(|code| {
+            let Some(glyph) = subtable.glyph_index(code) else {
+                return;
+            };
+            let code = match code {
+                code if symbol && code >= SYMBOL_OFFSET => code - SYMBOL_OFFSET,
+                code => code,
+            };
+            // private use chars carry no meaning, a later subtable may do better
+            match char::from_u32(code) {
+                Some(ch) if !is_private_use(ch) => {
+                    res.entry(glyph.0).or_insert(ch);
+                }
+                _ => {}
+            }
+        });
+    }
+    for glyph in 0..face.number_of_glyphs() {
+        if res.contains_key(&glyph) {
+            continue;
+        }
+        let ch = face.glyph_name(GlyphId(glyph)).and_then(char_of_name);
+        if let Some(ch) = ch {
+            res.insert(glyph, ch);
+        }
+    }
+    res
+}
+
+fn is_symbol(subtable: &Subtable) -> bool {
+    subtable.platform_id == PlatformId::Windows && subtable.encoding_id == 0
+}
+
+pub(crate) fn is_private_use(ch: char) -> bool {
+    matches!(ch as u32, 0xe000..=0xf8ff | 0xf0000..)
+}
+
+/// the char of a glyph name, like `a`, `comma`, `uni4E2D` or `u1F600`
+fn char_of_name(name: &str) -> Option<char> {
+    // `a.sc`, `f_i` and the like are variants or ligatures, keep the base
+    let name = name.split(['.', '_']).next()?;
+    let hex = name
+        .strip_prefix("uni")
+        .filter(|h| h.len() == 4)
+        .or_else(|| {
+            name.strip_prefix('u')
+                .filter(|h| (4..=6).contains(&h.len()))
+        });
+    if let Some(hex) = hex {
+        return u32::from_str_radix(hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .filter(|ch| !is_private_use(*ch));
+    }
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) if ch.is_ascii_alphabetic() => Some(ch),
+        _ => GLYPH_NAMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, ch)| *ch),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics() {
+        let data = std::fs::read("../samples/002/Doc_0/Res/font_13132.ttf").unwrap();
+        let font = FontInfo::new("Times New Roman".into(), Some(data));
+        let metrics = font.metrics();
+        assert!(metrics.ascent() > 0.5 && metrics.ascent() < 1.2);
+        assert!(metrics.descent() > 0.0 && metrics.descent() < 0.5);
+
+        let estimated = FontInfo::new("none".into(), Some(b"not a font".to_vec()));
+        let metrics = estimated.metrics();
+        assert_eq!(metrics.ascent(), DEFAULT_ASCENT);
+        assert_eq!(metrics.advance('a', None), None);
+    }
+
+    #[test]
+    fn test_to_unicode() {
+        let data = std::fs::read("../samples/002/Doc_0/Res/font_91_91.ttf").unwrap();
+        let font = FontInfo::new("宋体".into(), Some(data));
+        let text = [2591, 1553, 4537, 1944, 9087, 16376, 3224]
+            .iter()
+            .map(|g| font.to_unicode(*g).unwrap_or('?'))
+            .collect::<String>();
+        assert_eq!(text, "可信安全浏览器");
+        assert_eq!(font.to_unicode(u16::MAX), None);
+
+        let font = FontInfo::new("none".into(), None);
+        assert_eq!(font.to_unicode(3), None);
+        font.learn(3, 'a');
+        font.learn(3, 'b');
+        font.learn(4, '\u{e000}');
+        assert_eq!(font.to_unicode(4), None);
+        assert_eq!(font.to_unicode(3), Some('a'));
+    }
+
+    #[test]
+    fn test_char_of_name() {
+        assert_eq!(char_of_name("a"), Some('a'));
+        assert_eq!(char_of_name("comma"), Some(','));
+        assert_eq!(char_of_name("uni4E2D"), Some('中'));
+        assert_eq!(char_of_name("u1F600"), Some('😀'));
+        assert_eq!(char_of_name("a.sc"), Some('a'));
+        assert_eq!(char_of_name("uniE000"), None);
+        assert_eq!(char_of_name(".notdef"), None);
+        assert_eq!(char_of_name("glyph12"), None);
+    }
+}
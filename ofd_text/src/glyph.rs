@@ -0,0 +1,460 @@
+use crate::color::{to_hex, BLACK};
+use crate::error::{Error, Result};
+use crate::font::{is_private_use, FontInfo};
+use crate::positioned::BoundingBox;
+use crate::transform::Matrix;
+use ofd_base::file::annotation::AnnotationXmlFile;
+use ofd_base::file::page::{PageXmlFile, TextCode, TextObject, TextVal, VtGraphicUnit};
+use ofd_base::{StArray, StId, StRefId};
+use ofd_rw::Resources;
+use std::rc::Rc;
+
+/// `Relative` draw params followed at most
+const MAX_RELATIVE_DEPTH: usize = 8;
+
+/// a character placed on a page, in millimetres of the page
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Glyph {
+    /// usually one char
+    pub text: String,
+    /// `ID` of the text object
+    pub object_id: StId,
+    /// origin on the baseline
+    pub origin: (f32, f32),
+    /// advance along the baseline
+    pub advance: (f32, f32),
+    /// from the origin to the top of the glyph
+    pub ascent: (f32, f32),
+    /// from the origin to the bottom of the glyph
+    pub descent: (f32, f32),
+    /// font size on the page
+    pub size: f32,
+    pub font_name: String,
+    /// fill color as `#rrggbb`
+    pub color: String,
+}
+
+impl Glyph {
+    /// the axis aligned box around the glyph
+    pub(crate) fn bbox(&self) -> BoundingBox {
+        let (ox, oy) = self.origin;
+        let corners = [
+            (ox + self.ascent.0, oy + self.ascent.1),
+            (ox + self.descent.0, oy + self.descent.1),
+            (
+                ox + self.advance.0 + self.ascent.0,
+                oy + self.advance.1 + self.ascent.1,
+            ),
+            (
+                ox + self.advance.0 + self.descent.0,
+                oy + self.advance.1 + self.descent.1,
+            ),
+        ];
+        let min_x = corners.iter().map(|c| c.0).fold(f32::INFINITY, f32::min);
+        let min_y = corners.iter().map(|c| c.1).fold(f32::INFINITY, f32::min);
+        let max_x = corners
+            .iter()
+            .map(|c| c.0)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let max_y = corners
+            .iter()
+            .map(|c| c.1)
+            .fold(f32::NEG_INFINITY, f32::max);
+        BoundingBox {
+            x: min_x,
+            y: min_y,
+            w: max_x - min_x,
+            h: max_y - min_y,
+        }
+    }
+}
+
+/// a line of glyphs from `(x, y)`, one per char of `text`
+#[cfg(test)]
+pub(crate) fn glyph_line(
+    text: &str,
+    (x, y): (f32, f32),
+    advance: f32,
+    object_id: StId,
+) -> Vec<Glyph> {
+    text.chars()
+        .enumerate()
+        .map(|(i, c)| Glyph {
+            text: c.into(),
+            object_id,
+            origin: (x + i as f32 * advance, y),
+            advance: (advance, 0.0),
+            ascent: (0.0, -3.5),
+            descent: (0.0, 0.5),
+            size: 4.0,
+            font_name: String::new(),
+            color: "#000000".into(),
+        })
+        .collect()
+}
+
+/// text objects on a page, its templates go first
+///
+/// `fonts` looks up a font resource by id.
+pub(crate) fn page_glyphs<F>(
+    templates: &[&PageXmlFile],
+    page: &PageXmlFile,
+    annotations: &[&AnnotationXmlFile],
+    resources: &Resources,
+    fonts: F,
+) -> Result<Vec<Glyph>>
+where
+    F: FnMut(StRefId) -> Rc<FontInfo>,
+{
+    let mut walker = GlyphWalker {
+        resources,
+        fonts,
+        glyphs: vec![],
+    };
+    for content in templates.iter().chain([&page]) {
+        for layer in content.content.iter().flat_map(|c| c.layer.iter()) {
+            let color = walker.draw_param_color(layer.draw_param);
+            let color = color.unwrap_or_else(|| BLACK.into());
+            walker.push_objects(layer.objects.as_deref(), &Matrix::IDENTITY, &color)?;
+        }
+    }
+    for annot in annotations.iter().flat_map(|a| a.annot.iter()) {
+        if !annot.visible.unwrap_or(true) {
+            continue;
+        }
+        let appearance = &annot.appearance;
+        let m = Matrix::translate(appearance.boundary.x, appearance.boundary.y);
+        walker.push_objects(appearance.objects.as_deref(), &m, BLACK)?;
+    }
+    Ok(walker.glyphs)
+}
+
+struct GlyphWalker<'a, F> {
+    resources: &'a Resources,
+    fonts: F,
+    glyphs: Vec<Glyph>,
+}
+
+impl<F: FnMut(StRefId) -> Rc<FontInfo>> GlyphWalker<'_, F> {
+    /// objects and the objects in page blocks,
+    /// composite objects are skipped
+    fn push_objects(
+        &mut self,
+        objects: Option<&[VtGraphicUnit]>,
+        parent: &Matrix,
+        color: &str,
+    ) -> Result<()> {
+        for object in objects.into_iter().flatten() {
+            match object {
+                VtGraphicUnit::TextObject(text) if text.visible.unwrap_or(true) => {
+                    self.push_text_object(text, parent, color)?;
+                }
+                VtGraphicUnit::PageBlock(block) => {
+                    self.push_objects(block.objects.as_deref(), parent, color)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn push_text_object(&mut self, text: &TextObject, parent: &Matrix, color: &str) -> Result<()> {
+        let m = Matrix::for_object(text.boundary, text.ctm.as_ref()).then(parent);
+        let font = (self.fonts)(text.font);
+        let metrics = font.metrics();
+        let color = text
+            .fill_color
+            .as_ref()
+            .and_then(|c| to_hex(c, self.resources))
+            .or_else(|| self.draw_param_color(text.draw_param))
+            .unwrap_or_else(|| color.into());
+        let h_scale = text.h_scale.unwrap_or(1.0);
+        // y goes down
+        let ascent = m.apply_vector(0.0, -metrics.ascent() * text.size);
+        let descent = m.apply_vector(0.0, metrics.descent() * text.size);
+
+        let mut last = (0.0, 0.0);
+        for text_val in text.text_vals.iter() {
+            let text_code = &text_val.text_code;
+            let origin = (text_code.x.unwrap_or(last.0), text_code.y.unwrap_or(last.1));
+            let codes = char_codes(text_val, &font);
+            let advance_of = |i: usize| {
+                let (ch, glyph) = codes[i];
+                let advance = metrics
+                    .advance(ch, glyph)
+                    .map(|a| a * text.size)
+                    .unwrap_or_else(|| estimate_advance(ch, text.size));
+                advance * h_scale
+            };
+            let positions = char_positions(text_code, origin, codes.len(), advance_of)?;
+            for ((ch, _), (pos, advance)) in codes.iter().zip(positions) {
+                self.glyphs.push(Glyph {
+                    text: ch.to_string(),
+                    object_id: text.id,
+                    origin: m.apply(pos.0, pos.1),
+                    advance: m.apply_vector(advance, 0.0),
+                    ascent,
+                    descent,
+                    size: text.size * m.scale(),
+                    font_name: font.name.clone(),
+                    color: color.clone(),
+                });
+            }
+            last = origin;
+        }
+        Ok(())
+    }
+
+    /// fill color of a draw param, following `Relative`
+    fn draw_param_color(&self, id: Option<StRefId>) -> Option<String> {
+        let mut id = id?;
+        for _ in 0..MAX_RELATIVE_DEPTH {
+            let draw_param = self.resources.get_draw_param_by_id(id)?;
+            if let Some(c) = draw_param.fill_color.as_ref() {
+                return to_hex(c, self.resources);
+            }
+            id = draw_param.relative?;
+        }
+        None
+    }
+}
+
+/// chars of a text code, with the glyphs given by `CGTransform`
+///
+/// an empty text code takes its chars from the glyphs, and so do private use
+/// chars, see [FontInfo::to_unicode]. glyphs unknown to the font become `�`.
+fn char_codes(text_val: &TextVal, font: &FontInfo) -> Vec<(char, Option<u16>)> {
+    let cg_transform = text_val.cg_transform.iter().flatten();
+    if text_val.text_code.val.is_empty() {
+        return cg_transform
+            .flat_map(|cgt| {
+                cgt.glyphs
+                    .iter()
+                    .take(cgt.glyph_count.unwrap_or(1) as usize)
+            })
+            .map(|g| {
+                let ch = font.to_unicode(*g);
+                (ch.unwrap_or(char::REPLACEMENT_CHARACTER), Some(*g))
+            })
+            .collect();
+    }
+
+    let mut codes = text_val
+        .text_code
+        .val
+        .chars()
+        .map(|c| (c, None))
+        .collect::<Vec<_>>();
+    for cgt in cg_transform {
+        let start = cgt.code_position as usize;
+        let code_count = cgt.code_count.unwrap_or(1) as usize;
+        let glyph_count = cgt.glyph_count.unwrap_or(1) as usize;
+        let glyphs = cgt.glyphs.iter().take(glyph_count);
+        for (code, glyph) in codes.iter_mut().skip(start).take(code_count).zip(glyphs) {
+            code.1 = Some(*glyph);
+            // one to one, not a ligature
+            if code_count == glyph_count {
+                font.learn(*glyph, code.0);
+            }
+        }
+    }
+    for (ch, glyph) in codes.iter_mut() {
+        if let Some(glyph) = glyph.filter(|_| is_private_use(*ch)) {
+            *ch = font.to_unicode(glyph).unwrap_or(*ch);
+        }
+    }
+    codes
+}
+
+/// origin and advance of each char in a text code, in the object space
+///
+/// chars without a `DeltaX` advance by `advance_of`.
+fn char_positions(
+    text_code: &TextCode,
+    origin: (f32, f32),
+    count: usize,
+    advance_of: impl Fn(usize) -> f32,
+) -> Result<Vec<((f32, f32), f32)>> {
+    let expand = |deltas: &StArray<String>| expand_deltas(deltas, count);
+    let dxs = text_code.delta_x.as_ref().map(expand).transpose()?;
+    let dys = text_code.delta_y.as_ref().map(expand).transpose()?;
+    let dxs = dxs.unwrap_or_default();
+    let dys = dys.unwrap_or_default();
+
+    let mut positions = vec![];
+    let mut pos = origin;
+    for i in 0..count {
+        let advance = dxs.get(i).copied().unwrap_or_else(|| advance_of(i));
+        positions.push((pos, advance));
+        pos = (pos.0 + advance, pos.1 + dys.get(i).copied().unwrap_or(0.0));
+    }
+    Ok(positions)
+}
+
+/// rough width of a char, full width for CJK and the like
+pub(crate) fn estimate_advance(ch: char, size: f32) -> f32 {
+    if ch.is_ascii() {
+        size * 0.5
+    } else {
+        size
+    }
+}
+
+/// expand `DeltaX` or `DeltaY`, `g 3 1.5` stands for `1.5 1.5 1.5`
+///
+/// at most `max` deltas are kept, the rest are only checked.
+pub(crate) fn expand_deltas(deltas: &StArray<String>, max: usize) -> Result<Vec<f32>> {
+    let invalid = || Error::InvalidDelta(deltas.to_string());
+    let mut res = vec![];
+    let mut iter = deltas.iter();
+    while let Some(d) = iter.next() {
+        if d == "g" {
+            let count = iter
+                .next()
+                .and_then(|c| c.parse::<usize>().ok())
+                .ok_or_else(invalid)?;
+            let val = iter
+                .next()
+                .and_then(|v| v.parse::<f32>().ok())
+                .ok_or_else(invalid)?;
+            let room = max.saturating_sub(res.len());
+            res.extend(std::iter::repeat_n(val, count.min(room)));
+        } else {
+            let val = d.parse().map_err(|_| invalid())?;
+            if res.len() < max {
+                res.push(val);
+            }
+        }
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ofd_base::file::page::CGTransform;
+
+    fn deltas(s: &str) -> StArray<String> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_expand_deltas() {
+        assert_eq!(
+            expand_deltas(&deltas("1 g 3 1.5 2"), 9).unwrap(),
+            vec![1.0, 1.5, 1.5, 1.5, 2.0]
+        );
+        assert_eq!(
+            expand_deltas(&deltas("1 g 3 1.5 2"), 3).unwrap(),
+            vec![1.0, 1.5, 1.5]
+        );
+        assert!(matches!(
+            expand_deltas(&deltas("g 3"), 9),
+            Err(Error::InvalidDelta(_))
+        ));
+        assert!(expand_deltas(&deltas("1 x"), 9).is_err());
+        assert!(expand_deltas(&deltas("1 2 x"), 1).is_err());
+        // a huge run is cut to the chars there are
+        assert_eq!(
+            expand_deltas(&deltas("g 4000000000 1 2"), 2).unwrap(),
+            vec![1.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn test_char_positions() {
+        let text_code = TextCode {
+            x: Some(1.0),
+            y: Some(2.0),
+            delta_x: Some(deltas("g 2 3")),
+            delta_y: Some(deltas("0 1")),
+            val: "abcd".into(),
+        };
+        let positions = char_positions(&text_code, (1.0, 2.0), 4, |_| 2.0).unwrap();
+        let origins = positions.iter().map(|p| p.0).collect::<Vec<_>>();
+        assert_eq!(
+            origins,
+            vec![(1.0, 2.0), (4.0, 2.0), (7.0, 3.0), (9.0, 3.0)]
+        );
+        // no delta for the last two
+        assert_eq!(positions[3].1, 2.0);
+    }
+
+    #[test]
+    fn test_char_codes() {
+        let text_val = TextVal {
+            cg_transform: Some(vec![CGTransform {
+                code_position: 1,
+                code_count: Some(2),
+                glyph_count: Some(2),
+                glyphs: StArray(vec![7, 8]),
+            }]),
+            text_code: TextCode {
+                x: None,
+                y: None,
+                delta_x: None,
+                delta_y: None,
+                val: "abcd".into(),
+            },
+        };
+        let font = FontInfo::default();
+        assert_eq!(
+            char_codes(&text_val, &font),
+            vec![('a', None), ('b', Some(7)), ('c', Some(8)), ('d', None)]
+        );
+        assert_eq!(font.to_unicode(8), Some('c'));
+    }
+
+    #[test]
+    fn test_char_codes_from_glyphs() {
+        let data = std::fs::read("../samples/002/Doc_0/Res/font_91_91.ttf").unwrap();
+        let font = FontInfo::new("宋体".into(), Some(data));
+        let mut text_val = TextVal {
+            cg_transform: Some(vec![CGTransform {
+                code_position: 0,
+                code_count: Some(8),
+                glyph_count: Some(8),
+                glyphs: StArray(vec![2591, 1553, 4537, 1944, 9087, 16376, 3224, u16::MAX]),
+            }]),
+            text_code: TextCode {
+                x: Some(0.0),
+                y: Some(21.6653),
+                delta_x: None,
+                delta_y: None,
+                val: "".into(),
+            },
+        };
+        let text = char_codes(&text_val, &font)
+            .iter()
+            .map(|c| c.0)
+            .collect::<String>();
+        assert_eq!(text, "可信安全浏览器\u{fffd}");
+
+        // private use chars are taken from the glyphs too
+        text_val.text_code.val = "可\u{e000}".into();
+        let codes = char_codes(&text_val, &font);
+        assert_eq!(codes, vec![('可', Some(2591)), ('信', Some(1553))]);
+    }
+
+    #[test]
+    fn test_bbox() {
+        let glyph = Glyph {
+            text: "a".into(),
+            object_id: 1,
+            origin: (10.0, 20.0),
+            advance: (2.0, 0.0),
+            ascent: (0.0, -3.0),
+            descent: (0.0, 1.0),
+            size: 4.0,
+            font_name: String::new(),
+            color: BLACK.into(),
+        };
+        let expected = BoundingBox {
+            x: 10.0,
+            y: 17.0,
+            w: 2.0,
+            h: 4.0,
+        };
+        assert_eq!(glyph.bbox(), expected);
+    }
+}
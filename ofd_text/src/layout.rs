@@ -0,0 +1,149 @@
+use crate::glyph::Glyph;
+
+/// baselines closer than this, in font sizes, are on the same line
+const LINE_TOLERANCE: f32 = 0.5;
+/// gaps wider than this, in font sizes, are taken as a space
+const SPACE_GAP: f32 = 0.3;
+/// lines further apart than this, in font sizes, start a new paragraph
+const PARAGRAPH_GAP: f32 = 1.8;
+
+/// text of a page in reading order
+#[derive(Debug, Clone, Default)]
+pub struct PageText {
+    pub doc_index: usize,
+    pub page_index: usize,
+    pub paragraphs: Vec<Paragraph>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Paragraph {
+    pub lines: Vec<TextLine>,
+}
+
+/// glyphs sharing a baseline, left to right
+#[derive(Debug, Clone)]
+pub struct TextLine {
+    pub text: String,
+    /// left end of the line, in millimetres of the page
+    pub x: f32,
+    /// the baseline, in millimetres from the top of the page
+    pub y: f32,
+    /// the largest font size on the line
+    pub size: f32,
+}
+
+impl PageText {
+    /// paragraphs separated by an empty line
+    pub fn text(&self) -> String {
+        self.paragraphs
+            .iter()
+            .map(Paragraph::text)
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+impl Paragraph {
+    /// lines separated by `\n`
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|l| l.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// group glyphs into lines, top to bottom, then lines into paragraphs
+pub(crate) fn layout(glyphs: Vec<Glyph>) -> Vec<Paragraph> {
+    let mut paragraphs: Vec<Paragraph> = vec![];
+    let mut last: Option<(f32, f32)> = None;
+    for line in lines(glyphs).into_iter().filter_map(text_line) {
+        let gap = last.map(|(y, size)| line.y - y > size.max(line.size) * PARAGRAPH_GAP);
+        last = Some((line.y, line.size));
+        match paragraphs.last_mut() {
+            Some(p) if gap == Some(false) => p.lines.push(line),
+            _ => paragraphs.push(Paragraph { lines: vec![line] }),
+        }
+    }
+    paragraphs
+}
+
+/// group glyphs into lines, top to bottom, each one left to right
+pub(crate) fn lines(mut glyphs: Vec<Glyph>) -> Vec<Vec<Glyph>> {
+    glyphs.sort_by(|a, b| a.origin.1.total_cmp(&b.origin.1));
+    let mut lines: Vec<Vec<Glyph>> = vec![];
+    for glyph in glyphs {
+        match lines.last_mut() {
+            Some(line) if same_line(line, &glyph) => line.push(glyph),
+            _ => lines.push(vec![glyph]),
+        }
+    }
+    for line in lines.iter_mut() {
+        line.sort_by(|a, b| a.origin.0.total_cmp(&b.origin.0));
+    }
+    lines
+}
+
+/// whether a space is missing between two glyphs on a line
+pub(crate) fn space_between(prev: &Glyph, glyph: &Glyph) -> bool {
+    let gap = glyph.origin.0 - (prev.origin.0 + prev.advance.0);
+    !glyph.text.trim().is_empty() && gap > glyph.size * SPACE_GAP
+}
+
+fn same_line(line: &[Glyph], glyph: &Glyph) -> bool {
+    let y = line.iter().map(|g| g.origin.1).sum::<f32>() / line.len() as f32;
+    let size = line.iter().map(|g| g.size).fold(glyph.size, f32::min);
+    (glyph.origin.1 - y).abs() <= size * LINE_TOLERANCE
+}
+
+/// `None` for a line of blanks
+fn text_line(glyphs: Vec<Glyph>) -> Option<TextLine> {
+    let mut text = String::new();
+    let mut prev: Option<&Glyph> = None;
+    for g in glyphs.iter() {
+        if prev.is_some_and(|p| space_between(p, g)) && !text.ends_with(' ') {
+            text.push(' ');
+        }
+        text.push_str(&g.text);
+        prev = Some(g);
+    }
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return None;
+    }
+    Some(TextLine {
+        text,
+        x: glyphs[0].origin.0,
+        y: glyphs.iter().map(|g| g.origin.1).sum::<f32>() / glyphs.len() as f32,
+        size: glyphs.iter().map(|g| g.size).fold(0.0, f32::max),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glyph::glyph_line;
+
+    #[test]
+    fn test_layout() {
+        let mut all = vec![];
+        // out of order, a slightly lower baseline, and a gap for a space
+        all.extend(glyph_line("world", (30.0, 10.2), 2.0, 1));
+        all.extend(glyph_line("hello", (10.0, 10.0), 2.0, 1));
+        all.extend(glyph_line("next", (10.0, 15.0), 2.0, 1));
+        all.extend(glyph_line("   ", (10.0, 20.0), 2.0, 1));
+        all.extend(glyph_line("new paragraph", (10.0, 40.0), 2.0, 1));
+        let paragraphs = layout(all);
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(paragraphs[0].text(), "hello world\nnext");
+        assert_eq!(paragraphs[0].lines[0].x, 10.0);
+        assert_eq!(paragraphs[1].text(), "new paragraph");
+
+        let page = PageText {
+            paragraphs,
+            ..Default::default()
+        };
+        assert_eq!(page.text(), "hello world\nnext\n\nnew paragraph");
+    }
+}
@@ -0,0 +1,15 @@
+mod color;
+mod error;
+mod extract;
+mod font;
+mod glyph;
+mod layout;
+mod positioned;
+mod search;
+mod transform;
+
+pub use error::{Error, Result};
+pub use extract::*;
+pub use layout::*;
+pub use positioned::*;
+pub use search::*;
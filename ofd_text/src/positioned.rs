@@ -0,0 +1,101 @@
+use crate::glyph::Glyph;
+use ofd_base::{StBox, StId};
+use serde::Serialize;
+
+/// characters of a page with their boxes, lengths are in millimetres
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionedPage {
+    pub doc_index: usize,
+    pub page_index: usize,
+    /// size of `PhysicalBox`
+    pub width: f32,
+    pub height: f32,
+    /// in drawing order, templates first
+    pub chars: Vec<PositionedChar>,
+}
+
+/// a rectangle on a page, from the top left corner
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BoundingBox {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionedChar {
+    pub text: String,
+    pub page_index: usize,
+    /// the box of the glyph, with the ascent and descent of the font
+    pub bbox: BoundingBox,
+    pub font_name: String,
+    /// font size on the page
+    pub font_size: f32,
+    /// fill color as `#rrggbb`
+    pub color: String,
+    /// `ID` of the text object
+    pub object_id: StId,
+}
+
+impl BoundingBox {
+    /// the smallest box holding both
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        BoundingBox {
+            x,
+            y,
+            w: (self.x + self.w).max(other.x + other.w) - x,
+            h: (self.y + self.h).max(other.y + other.h) - y,
+        }
+    }
+}
+
+impl From<BoundingBox> for StBox {
+    fn from(bbox: BoundingBox) -> Self {
+        StBox::from((bbox.x, bbox.y, bbox.w, bbox.h))
+    }
+}
+
+impl PositionedChar {
+    pub(crate) fn new(page_index: usize, glyph: Glyph) -> Self {
+        Self {
+            page_index,
+            bbox: glyph.bbox(),
+            text: glyph.text,
+            font_name: glyph.font_name,
+            font_size: glyph.size,
+            color: glyph.color,
+            object_id: glyph.object_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_union() {
+        let a = BoundingBox {
+            x: 1.0,
+            y: 1.0,
+            w: 2.0,
+            h: 2.0,
+        };
+        let b = BoundingBox {
+            x: 2.0,
+            y: 0.0,
+            w: 2.0,
+            h: 2.0,
+        };
+        let expected = BoundingBox {
+            x: 1.0,
+            y: 0.0,
+            w: 3.0,
+            h: 3.0,
+        };
+        assert_eq!(a.union(&b), expected);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::error::Result;
+use crate::extract::TextExtractor;
+use crate::glyph::Glyph;
+use crate::layout::{lines, space_between};
+use crate::positioned::BoundingBox;
+use ofd_base::StId;
+use ofd_rw::{Ofd, Storage};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// off by default
+    pub case_sensitive: bool,
+    /// take the query as a regular expression,
+    /// it is matched against the text with whitespace collapsed to one space
+    pub regex: bool,
+}
+
+/// a match on a page, lengths are in millimetres
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub doc_index: usize,
+    pub page_index: usize,
+    /// the matched text
+    pub text: String,
+    /// `ID` of the text objects holding the match, in reading order
+    pub object_ids: Vec<StId>,
+    /// the box around the whole match
+    pub bbox: BoundingBox,
+    /// a box for each line of the match, to draw the highlight
+    pub rects: Vec<BoundingBox>,
+}
+
+/// find text in all the documents and pages of an ofd
+///
+/// plain queries ignore whitespace, `a b` matches both `ab` and `a  b`.
+pub fn search<S: Storage>(
+    ofd: &Ofd<S>,
+    query: &str,
+    options: &SearchOptions,
+) -> Result<Vec<SearchHit>> {
+    TextExtractor::new(ofd.clone()).search(query, options)
+}
+
+/// `None` for a blank query
+pub(crate) fn pattern(query: &str, options: &SearchOptions) -> Result<Option<Regex>> {
+    let pattern = if options.regex {
+        query.to_string()
+    } else {
+        // spaces may sit between any two chars, as the layout adds them
+        query
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| regex::escape(c.encode_utf8(&mut [0; 4])))
+            .collect::<Vec<_>>()
+            .join(" ?")
+    };
+    if pattern.trim().is_empty() {
+        return Ok(None);
+    }
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()?;
+    Ok(Some(regex))
+}
+
+/// matches of `pattern` in the glyphs of a page
+pub(crate) fn find(
+    pattern: &Regex,
+    glyphs: Vec<Glyph>,
+    doc_index: usize,
+    page_index: usize,
+) -> Vec<SearchHit> {
+    let haystack = Haystack::new(glyphs);
+    let mut hits = vec![];
+    for m in pattern.find_iter(&haystack.text) {
+        // glyphs in the match, spaces added by the layout have none
+        let mut matched: Vec<usize> = haystack.owners[m.range()]
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+        matched.dedup();
+        if matched.is_empty() {
+            continue;
+        }
+        let mut object_ids = vec![];
+        let mut rects: Vec<(usize, BoundingBox)> = vec![];
+        for &i in matched.iter() {
+            let (line, glyph) = &haystack.glyphs[i];
+            if !object_ids.contains(&glyph.object_id) {
+                object_ids.push(glyph.object_id);
+            }
+            let bbox = glyph.bbox();
+            match rects.last_mut() {
+                Some((l, rect)) if l == line => *rect = rect.union(&bbox),
+                _ => rects.push((*line, bbox)),
+            }
+        }
+        let rects = rects.into_iter().map(|r| r.1).collect::<Vec<_>>();
+        let bbox = rects.iter().skip(1).fold(rects[0], |a, b| a.union(b));
+        hits.push(SearchHit {
+            doc_index,
+            page_index,
+            text: m.as_str().to_string(),
+            object_ids,
+            bbox,
+            rects,
+        });
+    }
+    hits
+}
+
+/// text of a page in reading order, with whitespace collapsed
+struct Haystack {
+    text: String,
+    /// the glyph each byte of `text` comes from
+    owners: Vec<Option<usize>>,
+    /// glyphs with the index of their line
+    glyphs: Vec<(usize, Glyph)>,
+}
+
+impl Haystack {
+    fn new(glyphs: Vec<Glyph>) -> Self {
+        let mut haystack = Haystack {
+            text: String::new(),
+            owners: vec![],
+            glyphs: vec![],
+        };
+        for (line_index, line) in lines(glyphs).into_iter().enumerate() {
+            haystack.push(' ', None);
+            for (i, glyph) in line.iter().enumerate() {
+                if i > 0 && space_between(&line[i - 1], glyph) {
+                    haystack.push(' ', None);
+                }
+                let owner = haystack.glyphs.len() + i;
+                for ch in glyph.text.chars() {
+                    haystack.push(ch, Some(owner));
+                }
+            }
+            haystack
+                .glyphs
+                .extend(line.into_iter().map(|g| (line_index, g)));
+        }
+        haystack
+    }
+
+    /// whitespace is collapsed to one space, none at the start
+    fn push(&mut self, ch: char, owner: Option<usize>) {
+        let ch = match ch.is_whitespace() {
+            true if self.text.is_empty() || self.text.ends_with(' ') => return,
+            true => ' ',
+            false => ch,
+        };
+        self.text.push(ch);
+        self.owners
+            .extend(std::iter::repeat_n(owner, ch.len_utf8()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glyph::glyph_line;
+    use eyre::Result;
+    use ofd_rw::from_path;
+
+    #[test]
+    fn test_find() -> Result<()> {
+        let mut all = glyph_line("Hello", (10.0, 10.0), 2.0, 10);
+        all.extend(glyph_line("World", (30.0, 10.0), 2.0, 10));
+        all.extend(glyph_line("again", (10.0, 20.0), 2.0, 20));
+        let options = SearchOptions::default();
+
+        let hits = find(
+            &pattern("helloworld", &options)?.unwrap(),
+            all.clone(),
+            0,
+            1,
+        );
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].text, "Hello World");
+        assert_eq!(hits[0].object_ids, vec![10]);
+        assert_eq!(hits[0].bbox.x, 10.0);
+        assert_eq!(hits[0].bbox.w, 30.0);
+
+        // across lines
+        let hits = find(
+            &pattern("world\nagain", &options)?.unwrap(),
+            all.clone(),
+            0,
+            1,
+        );
+        assert_eq!(hits[0].object_ids, vec![10, 20]);
+        assert_eq!(hits[0].rects.len(), 2);
+        assert_eq!(hits[0].bbox.h, 14.0);
+
+        let options = SearchOptions {
+            case_sensitive: true,
+            regex: true,
+        };
+        assert!(find(&pattern("hello", &options)?.unwrap(), all.clone(), 0, 1).is_empty());
+        let hits = find(&pattern(r"[A-Z]\w+", &options)?.unwrap(), all, 0, 1);
+        assert_eq!(hits.len(), 2);
+
+        assert!(pattern(" ", &SearchOptions::default())?.is_none());
+        assert!(pattern("(", &options).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_search() -> Result<()> {
+        let ofd = from_path("../samples/001.ofd")?;
+        let hits = search(&ofd, "财天下科技有限公司", &SearchOptions::default())?;
+        // on every page
+        let pages = hits.iter().map(|h| h.page_index).collect::<Vec<_>>();
+        assert_eq!(pages, vec![0, 1, 2, 3, 4]);
+
+        let ofd = from_path("../samples/000.ofd")?;
+        let hits = search(&ofd, "电子普通发票", &SearchOptions::default())?;
+        assert_eq!(hits.len(), 1);
+        let hit = &hits[0];
+        assert_eq!((hit.doc_index, hit.page_index), (0, 0));
+        assert_eq!(hit.text, "电子普通发票");
+        assert_eq!(hit.object_ids.len(), 1);
+        assert!(hit.bbox.x > 0.0 && hit.bbox.x + hit.bbox.w < 210.0);
+        assert!(hit.bbox.y > 0.0 && hit.bbox.y + hit.bbox.h < 140.0);
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use ofd_base::{StArray, StBox};
+
+/// an affine transform, `[a b c d e f]` as in `CTM`
+///
+/// maps `(x, y)` to `(a * x + c * y + e, b * x + d * y + f)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Matrix {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+    f: f32,
+}
+
+impl Matrix {
+    pub(crate) const IDENTITY: Matrix = Matrix::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    pub(crate) const fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    pub(crate) fn translate(x: f32, y: f32) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    /// `CTM` of an object, malformed ones are ignored
+    pub(crate) fn from_ctm(ctm: Option<&StArray<f32>>) -> Self {
+        match ctm.map(|m| m.as_slice()) {
+            Some(&[a, b, c, d, e, f]) => Self::new(a, b, c, d, e, f),
+            _ => Self::IDENTITY,
+        }
+    }
+
+    /// from the object space of a graphic unit to the space holding its boundary
+    pub(crate) fn for_object(boundary: StBox, ctm: Option<&StArray<f32>>) -> Self {
+        Self::from_ctm(ctm).then(&Self::translate(boundary.x, boundary.y))
+    }
+
+    /// `self` followed by `other`
+    pub(crate) fn then(&self, other: &Matrix) -> Matrix {
+        Matrix {
+            a: self.a * other.a + self.b * other.c,
+            b: self.a * other.b + self.b * other.d,
+            c: self.c * other.a + self.d * other.c,
+            d: self.c * other.b + self.d * other.d,
+            e: self.e * other.a + self.f * other.c + other.e,
+            f: self.e * other.b + self.f * other.d + other.f,
+        }
+    }
+
+    pub(crate) fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+
+    /// a vector, translation is not applied
+    pub(crate) fn apply_vector(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.a * x + self.c * y, self.b * x + self.d * y)
+    }
+
+    /// how lengths scale on average
+    pub(crate) fn scale(&self) -> f32 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix() {
+        let ctm = StArray(vec![0.0, 1.0, -1.0, 0.0, 2.0, 0.0]);
+        let boundary = StBox::from((10.0, 20.0, 5.0, 5.0));
+        let m = Matrix::for_object(boundary, Some(&ctm));
+        // rotated by 90 degrees, then moved
+        assert_eq!(m.apply(1.0, 0.0), (12.0, 21.0));
+        assert_eq!(m.apply_vector(1.0, 0.0), (0.0, 1.0));
+        assert_eq!(m.scale(), 1.0);
+
+        let m = Matrix::from_ctm(Some(&StArray(vec![0.5, 0.0, 0.0, 0.5, 0.0, 0.0])));
+        assert_eq!(m.scale(), 0.5);
+        assert_eq!(
+            Matrix::from_ctm(Some(&StArray(vec![1.0]))),
+            Matrix::IDENTITY
+        );
+    }
+}
@@ -5,6 +5,7 @@ mod value;
 
 use crate::de::key::KeyDe;
 use crate::de::value::{AttrValueDe, TextValueDe, ValueDe};
+use crate::raw::{element_to_string, RAW_ELEMENT};
 use minidom::element::{Attrs, Texts};
 use minidom::{Children, Element};
 use serde::de::{DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor};
@@ -31,6 +32,97 @@ pub enum XmlDeError {
 
     #[error("operation not supported.")]
     NotSupported,
+
+    /// an error with where it happened
+    #[error("{source} at {}", location(.path, .attr))]
+    Located {
+        /// path of the element, like `/Page/Content/Layer[2]/TextObject[@ID=57]`
+        path: String,
+        /// the attribute failing, if any
+        attr: Option<String>,
+        source: Box<XmlDeError>,
+    },
+}
+
+fn location(path: &str, attr: &Option<String>) -> String {
+    match attr {
+        Some(attr) => format!("{path}/@{attr}"),
+        None => path.to_string(),
+    }
+}
+
+impl XmlDeError {
+    /// path of the element failing, like `/Page/Content/Layer[2]/TextObject[@ID=57]`
+    ///
+    /// elements with an `ID` attribute are identified by it,
+    /// others by their position among the siblings with the same name.
+    pub fn element_path(&self) -> Option<&str> {
+        match self {
+            XmlDeError::Located { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// name of the attribute failing
+    pub fn attr(&self) -> Option<&str> {
+        match self {
+            XmlDeError::Located { attr, .. } => attr.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// the error without location
+    pub fn cause(&self) -> &XmlDeError {
+        match self {
+            XmlDeError::Located { source, .. } => source,
+            e => e,
+        }
+    }
+
+    /// prepend the path of `ele` to the location
+    fn in_element(self, ele: &Element, parent: Option<&Element>) -> Self {
+        let segment = path_segment(ele, parent);
+        match self {
+            XmlDeError::Located { path, attr, source } => XmlDeError::Located {
+                path: segment + &path,
+                attr,
+                source,
+            },
+            e => XmlDeError::Located {
+                path: segment,
+                attr: None,
+                source: Box::new(e),
+            },
+        }
+    }
+
+    fn in_attr(self, name: &str) -> Self {
+        XmlDeError::Located {
+            path: String::new(),
+            attr: Some(name.into()),
+            source: Box::new(self),
+        }
+    }
+}
+
+/// `/Name[@ID=1]`, `/Name[2]` or `/Name`
+fn path_segment(ele: &Element, parent: Option<&Element>) -> String {
+    let name = ele.name();
+    if let Some(id) = ele.attr("ID") {
+        return format!("/{name}[@ID={id}]");
+    }
+    let siblings = parent
+        .map(|p| p.children().filter(|c| c.name() == name).collect::<Vec<_>>())
+        .unwrap_or_default();
+    if siblings.len() > 1 {
+        let index = siblings
+            .iter()
+            .position(|c| std::ptr::eq(*c, ele))
+            .unwrap_or_default();
+        format!("/{name}[{}]", index + 1)
+    } else {
+        format!("/{name}")
+    }
 }
 
 impl serde::de::Error for XmlDeError {
@@ -49,8 +141,8 @@ macro_rules! de_primitives {
             V: Visitor<'de>,
         {
             let v = self.input.text();
-            let parsed = v.parse::<$ty>()?;
-            visitor.$f2(parsed)
+            let parsed = v.parse::<$ty>().map_err(|e| self.locate(e.into()))?;
+            visitor.$f2(parsed).map_err(|e| self.locate(e))
         }
     };
 }
@@ -59,6 +151,9 @@ pub struct XmlDe<'de> {
     name: Option<String>,
     input: &'de Element,
     parent: Option<&'de Element>,
+    // whether errors get the path of `input`,
+    // false if another deserializer of the same element does it
+    locate: bool,
 }
 
 impl<'de> XmlDe<'de> {
@@ -67,6 +162,15 @@ impl<'de> XmlDe<'de> {
             name: None,
             input: ele,
             parent: None,
+            locate: true,
+        }
+    }
+
+    fn locate(&self, e: XmlDeError) -> XmlDeError {
+        if self.locate {
+            e.in_element(self.input, self.parent)
+        } else {
+            e
         }
     }
 }
@@ -114,20 +218,25 @@ impl<'de> Deserializer<'de> for &mut XmlDe<'de> {
         } else {
             Err(XmlDeError::Message("not a char".into()))
         }
+        .map_err(|e| self.locate(e))
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_str(self.input.text().as_str())
+        visitor
+            .visit_str(self.input.text().as_str())
+            .map_err(|e| self.locate(e))
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_string(self.input.text())
+        visitor
+            .visit_string(self.input.text())
+            .map_err(|e| self.locate(e))
     }
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -177,7 +286,13 @@ impl<'de> Deserializer<'de> for &mut XmlDe<'de> {
     where
         V: Visitor<'de>,
     {
-        todo!()
+        if name == RAW_ELEMENT {
+            let xml = element_to_string(self.input).map_err(XmlDeError::Message);
+            return xml
+                .and_then(|xml| visitor.visit_string(xml))
+                .map_err(|e| self.locate(e));
+        }
+        visitor.visit_newtype_struct(self)
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -229,7 +344,9 @@ impl<'de> Deserializer<'de> for &mut XmlDe<'de> {
 
         // let x = self.input.name();
         // assert_eq!(x, name);
-        visitor.visit_map(AttrChild::new(self, fields))
+        visitor
+            .visit_map(AttrChild::new(self, fields))
+            .map_err(|e| self.locate(e))
     }
 
     fn deserialize_enum<V>(
@@ -241,8 +358,11 @@ impl<'de> Deserializer<'de> for &mut XmlDe<'de> {
     where
         V: Visitor<'de>,
     {
-        let enum_access = Enum::new(self.input);
-        visitor.visit_enum(enum_access)
+        // the variant is deserialized from the same element
+        let enum_access = Enum::new(self.input, self.parent, false);
+        visitor
+            .visit_enum(enum_access)
+            .map_err(|e| self.locate(e))
     }
 
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -262,7 +382,7 @@ impl<'de> Deserializer<'de> for &mut XmlDe<'de> {
 
 enum Ctx<'de> {
     Empty,
-    Attr(&'de str),
+    Attr(&'de str, &'de str),
     Ele(&'de str, String),
     Text(&'de Element),
     Value,
@@ -337,7 +457,7 @@ impl<'de> MapAccess<'de> for AttrChild<'_, 'de> {
         // attrs
         let option = self.attrs.next();
         if let Some((key, value)) = option {
-            self.current_value = Ctx::Attr(value);
+            self.current_value = Ctx::Attr(key, value);
             let mut de = KeyDe::new_attr(key);
             let result = seed.deserialize(&mut de);
             return result.map(Some);
@@ -370,9 +490,9 @@ impl<'de> MapAccess<'de> for AttrChild<'_, 'de> {
             Ctx::Empty => Err(XmlDeError::Message(
                 "you must call next_key_seed first!".into(),
             )),
-            Ctx::Attr(s) => {
+            Ctx::Attr(key, s) => {
                 let mut de = AttrValueDe::new(s);
-                seed.deserialize(&mut de)
+                seed.deserialize(&mut de).map_err(|e| e.in_attr(key))
             }
             Ctx::Ele(name, ns) => {
                 // this unwrap should be fine
@@ -397,10 +517,25 @@ impl<'de> MapAccess<'de> for AttrChild<'_, 'de> {
 
 struct Enum<'de> {
     input: &'de Element,
+    parent: Option<&'de Element>,
+    locate: bool,
 }
 impl<'de> Enum<'de> {
-    fn new(input: &'de Element) -> Self {
-        Self { input }
+    fn new(input: &'de Element, parent: Option<&'de Element>, locate: bool) -> Self {
+        Self {
+            input,
+            parent,
+            locate,
+        }
+    }
+
+    fn de(&self) -> XmlDe<'de> {
+        XmlDe {
+            name: None,
+            input: self.input,
+            parent: self.parent,
+            locate: self.locate,
+        }
     }
 }
 
@@ -413,8 +548,11 @@ impl<'de> EnumAccess<'de> for Enum<'de> {
         V: DeserializeSeed<'de>,
     {
         let mut de = KeyDe::new_ele(self.input.name());
-        let result = seed.deserialize(&mut de)?;
-        Ok((result, self))
+        match seed.deserialize(&mut de) {
+            Ok(result) => Ok((result, self)),
+            Err(e) if self.locate => Err(e.in_element(self.input, self.parent)),
+            Err(e) => Err(e),
+        }
     }
 }
 
@@ -429,7 +567,7 @@ impl<'de> VariantAccess<'de> for Enum<'de> {
     where
         T: DeserializeSeed<'de>,
     {
-        let mut de = XmlDe::from_ele(self.input);
+        let mut de = self.de();
         seed.deserialize(&mut de)
     }
 
@@ -448,7 +586,7 @@ impl<'de> VariantAccess<'de> for Enum<'de> {
     where
         V: Visitor<'de>,
     {
-        let mut de = XmlDe::from_ele(self.input);
+        let mut de = self.de();
         de.deserialize_struct("", fields, visitor)
     }
 }
@@ -481,7 +619,7 @@ impl<'de> XmlSeqAccess<'de> {
         };
 
         Self {
-            parent: de.input,
+            parent: de.parent.unwrap_or(de.input),
             iter,
         }
     }
@@ -542,6 +680,7 @@ impl<'de> SeqAccess<'de> for XmlSeqAccess<'de> {
 
 #[cfg(test)]
 mod tests {
+    use crate::de::XmlDeError;
     use crate::{de::from_ele, init_tracing_subscriber};
     use eyre::Result;
     use minidom::Element;
@@ -663,6 +802,40 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_error_location() -> Result<()> {
+        #[derive(Debug, Deserialize)]
+        struct Page {
+            #[serde(rename = "Layer")]
+            layers: Vec<Layer>,
+        }
+        #[derive(Debug, Deserialize)]
+        struct Layer {
+            #[serde(rename = "$value")]
+            objects: Vec<Object>,
+        }
+        #[derive(Debug, Deserialize)]
+        enum Object {
+            Text(Foo),
+        }
+
+        let root: Element = r#"<Page xmlns=""><Layer><Text attr2="1"/></Layer><Layer><Text attr2="1"/><Text ID="57" attr2="x"/></Layer></Page>"#.parse()?;
+        let err = from_ele::<Page>(&root).unwrap_err();
+        assert_eq!(err.element_path(), Some("/Page/Layer[2]/Text[@ID=57]"));
+        assert_eq!(err.attr(), Some("attr2"));
+        assert!(matches!(err.cause(), XmlDeError::ParseFloat(_)));
+        assert_eq!(
+            err.to_string(),
+            "invalid float literal at /Page/Layer[2]/Text[@ID=57]/@attr2"
+        );
+
+        let root: Element = r#"<Page xmlns=""><Layer><Text/></Layer></Page>"#.parse()?;
+        let err = from_ele::<Page>(&root).unwrap_err();
+        assert_eq!(err.element_path(), Some("/Page/Layer/Text"));
+        assert_eq!(err.attr(), None);
+        Ok(())
+    }
+
     #[test]
     fn test_seq() -> Result<()> {
         init_tracing_subscriber();
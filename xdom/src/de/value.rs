@@ -721,7 +721,7 @@ impl<'de> Deserializer<'de> for &mut ValueDe<'de> {
         trace!("[$value] deserialize_enum {}", name);
 
         if let Some(e) = self.elements.first() {
-            let enum_access = Enum::new(e);
+            let enum_access = Enum::new(e, Some(self.parent), true);
             visitor.visit_enum(enum_access)
         } else {
             visitor.visit_none()
@@ -1,6 +1,9 @@
 pub mod de;
+mod raw;
 pub mod ser;
 
+pub use raw::RawElement;
+
 #[cfg(test)]
 pub(crate) fn init_tracing_subscriber() {
     use tracing_subscriber::{filter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
@@ -0,0 +1,121 @@
+use minidom::Element;
+use serde::de::Visitor;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Formatter;
+use std::ops::{Deref, DerefMut};
+
+/// magic name, [crate::de::XmlDe] and [crate::ser::XmlSer] pass the element as a string
+pub(crate) const RAW_ELEMENT: &str = "$xdom::RawElement";
+
+/// an element kept as it is, for `xs:anyType` content
+///
+/// it holds the whole element, including its own name and attributes.
+/// only works with the serializer and deserializer in this crate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawElement(pub Element);
+
+impl Deref for RawElement {
+    type Target = Element;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for RawElement {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<Element> for RawElement {
+    fn from(value: Element) -> Self {
+        Self(value)
+    }
+}
+
+impl Serialize for RawElement {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let xml = element_to_string(&self.0).map_err(S::Error::custom)?;
+        serializer.serialize_newtype_struct(RAW_ELEMENT, &xml)
+    }
+}
+
+impl<'de> Deserialize<'de> for RawElement {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(RAW_ELEMENT, RawElementVisitor)
+    }
+}
+
+struct RawElementVisitor;
+
+impl Visitor<'_> for RawElementVisitor {
+    type Value = RawElement;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("an xml element")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        v.parse::<Element>().map(RawElement).map_err(E::custom)
+    }
+}
+
+/// write an element to string, for [RAW_ELEMENT]
+pub(crate) fn element_to_string(element: &Element) -> Result<String, String> {
+    let mut buf = Vec::new();
+    element.write_to(&mut buf).map_err(|e| e.to_string())?;
+    String::from_utf8(buf).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::de::from_ele;
+    use crate::ser::XmlSer;
+    use eyre::Result;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Ext {
+        #[serde(rename = "@Name")]
+        name: String,
+        #[serde(rename = "$value")]
+        props: Vec<Prop>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    enum Prop {
+        Property(String),
+        Data(RawElement),
+    }
+
+    #[test]
+    fn test_raw_element() -> Result<()> {
+        let xml = r#"<Ext xmlns="urn:a" Name="n"><Property>p</Property><Data><v:Foo xmlns:v="urn:v" a="1">x<v:Bar/></v:Foo></Data></Ext>"#;
+        let root: Element = xml.parse()?;
+        let ext = from_ele::<Ext>(&root)?;
+        assert_eq!(ext.name, "n");
+        let Prop::Data(data) = &ext.props[1] else {
+            panic!("not data")
+        };
+        assert_eq!(data.name(), "Data");
+        let foo = data.get_child("Foo", "urn:v").unwrap();
+        assert_eq!(foo.attr("a"), Some("1"));
+        assert!(foo.get_child("Bar", "urn:v").is_some());
+
+        let ser = XmlSer::builder().name("Ext").ns("urn:a").build()?;
+        let back = ser.ser_to_element(&ext)?;
+        assert_eq!(&back, &root);
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 mod attr;
 
+use crate::raw::RAW_ELEMENT;
 use crate::ser::attr::AttrValueSer;
 use minidom::{Element, IntoAttributeValue, Node};
 use serde::ser::{Impossible, SerializeSeq, SerializeStruct, SerializeStructVariant};
@@ -335,6 +336,14 @@ impl Serializer for &mut XmlSer {
         T: ?Sized + Serialize,
     {
         trace!("{} {} serialize_newtype_struct {}", SER_TAG, self.uid, name);
+        if name == RAW_ELEMENT {
+            let xml = AttrValueSer::convert_to_string(&value)?.unwrap_or_default();
+            let element = xml
+                .parse::<Element>()
+                .map_err(|e| XmlSerErr::Message(e.to_string()))?;
+            self.output = Output::Ele(element);
+            return Ok(());
+        }
         value.serialize(self)
     }
 